@@ -0,0 +1,11 @@
+use clap::Parser;
+use sp1_ics07_tendermint_operator::{cli::command::mock_rpc::Args, runners};
+use sp1_sdk::utils::setup_logger;
+
+/// A mock CometBFT RPC server serving stored light blocks or a synthetic chain, to run the
+/// operator, `genesis` and `fixtures` end to end against localhost.
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    setup_logger();
+    runners::mock_rpc::run(Args::parse()).await
+}
@@ -1,10 +1,11 @@
 use clap::Parser;
 use sp1_ics07_tendermint_operator::{
-    cli::command::{fixtures, Commands, OperatorCli},
+    cli::command::{fixtures, job, Commands, OperatorCli},
     runners::{
         self,
         fixtures::{membership, uc_and_mem, update_client},
     },
+    watchdog,
 };
 use sp1_sdk::utils::setup_logger;
 
@@ -21,12 +22,34 @@ async fn main() -> anyhow::Result<()> {
 
     let cli = OperatorCli::parse();
     match cli.command {
-        Commands::Start(args) => runners::operator::run(args).await,
+        Commands::Start(args) => {
+            let result = runners::operator::run(args).await;
+            // Exit with a distinct code when a client expired or is about to, for alerting.
+            if let Err(e) = &result {
+                if let Some(code) = watchdog::exit_code(e) {
+                    log::error!("{e:#}");
+                    std::process::exit(code);
+                }
+            }
+            result
+        }
         Commands::Genesis(args) => runners::genesis::run(args).await,
         Commands::Fixtures(cmd) => match cmd.command {
             fixtures::Cmds::UpdateClient(args) => update_client::run(args).await,
             fixtures::Cmds::Membership(args) => membership::run(args).await,
             fixtures::Cmds::UpdateClientAndMembership(args) => uc_and_mem::run(args).await,
         },
+        Commands::Vkeys(args) => runners::vkeys::run(&args),
+        Commands::Job(cmd) => match cmd.command {
+            job::Cmds::UpdateClient(args) => runners::job::run_update_client(args).await,
+            job::Cmds::Membership(args) => runners::job::run_membership(args).await,
+            job::Cmds::Prove(args) => runners::job::run_prove(args).await,
+            job::Cmds::Submit(args) => runners::job::run_submit(args).await,
+        },
+        Commands::Coordinator(args) => runners::coordinator::run(args).await,
+        Commands::Worker(args) => runners::worker::run(args).await,
+        Commands::Verify(args) => runners::verify::run(&args),
+        Commands::LightBlock(args) => runners::light_block::run(args).await,
+        Commands::Analyze(args) => runners::analyze::run(args).await,
     }
 }
@@ -0,0 +1,139 @@
+//! Native skipping verification with bisection, to find a sequence of updates from a trusted
+//! height to a target height when the trust threshold is not met in a single step.
+
+use anyhow::Result;
+use tendermint::Time;
+use tendermint_light_client_verifier::{
+    options::Options, types::LightBlock, ProdVerifier, Verdict, Verifier,
+};
+
+use crate::rpc::{LightBlockProvider, TendermintRpcExt};
+
+/// Finds a sequence of light blocks from `trusted_light_block` to `target_light_block` where
+/// each light block verifies from the previous one, as the Tendermint light client does with
+/// skipping verification.
+///
+/// The target is tried first. Whenever the trusted validators that signed a light block do not
+/// meet the trust threshold, the light block halfway between the trusted height and the failed
+/// height is tried instead. The returned trace ends with the target light block, and is a single
+/// light block if the target verifies directly.
+///
+/// # Errors
+/// Returns an error if a light block cannot be fetched, if a light block is invalid, or if the
+/// trace needs more than `max_steps` light blocks.
+pub async fn verification_trace(
+    provider: &(impl LightBlockProvider + ?Sized),
+    trusted_light_block: &LightBlock,
+    target_light_block: LightBlock,
+    options: &Options,
+    max_steps: usize,
+) -> Result<Vec<LightBlock>> {
+    let verifier = ProdVerifier::default();
+    let target_height: u32 = target_light_block.height().value().try_into()?;
+    let now = Time::now();
+
+    let mut trace: Vec<LightBlock> = vec![];
+    let mut candidate = target_light_block.clone();
+    loop {
+        let trusted = trace.last().unwrap_or(trusted_light_block);
+        let trusted_height: u32 = trusted.height().value().try_into()?;
+        let candidate_height: u32 = candidate.height().value().try_into()?;
+
+        match verifier.verify_update_header(
+            candidate.as_untrusted_state(),
+            trusted.as_trusted_state(),
+            options,
+            now,
+        ) {
+            Verdict::Success => {
+                log::debug!("Light block {candidate_height} verifies from {trusted_height}");
+                trace.push(candidate);
+                if candidate_height == target_height {
+                    return Ok(trace);
+                }
+                anyhow::ensure!(
+                    trace.len() < max_steps,
+                    "bisection from height {} to {target_height} needs more than {max_steps} \
+                     updates",
+                    trusted_light_block.height()
+                );
+                candidate = target_light_block.clone();
+            }
+            Verdict::NotEnoughTrust(tally) => {
+                let pivot = trusted_height + (candidate_height - trusted_height) / 2;
+                log::info!(
+                    "Light block {candidate_height} does not verify from {trusted_height} \
+                     ({tally}), trying {pivot}"
+                );
+                anyhow::ensure!(
+                    pivot > trusted_height,
+                    "light block {candidate_height} does not verify from the adjacent light \
+                     block {trusted_height}: {tally}"
+                );
+                candidate = provider.get_light_block(Some(pivot)).await?;
+            }
+            Verdict::Invalid(detail) => {
+                anyhow::bail!(
+                    "light block {candidate_height} is invalid from {trusted_height}: {detail}"
+                );
+            }
+        }
+    }
+}
+
+/// Finds the furthest light block up to `target_height` that verifies from
+/// `trusted_light_block` in a single step, by binary search over the heights. This assumes that
+/// the power of the trusted validators signing the commits only decreases with the height.
+///
+/// # Errors
+/// Returns an error if a light block cannot be fetched, if a light block is invalid, for example
+/// because the trusting period of `trusted_light_block` elapsed, or if no light block verifies.
+pub async fn furthest_verifiable(
+    provider: &(impl LightBlockProvider + ?Sized),
+    trusted_light_block: &LightBlock,
+    target_height: u32,
+    options: &Options,
+) -> Result<LightBlock> {
+    let verifier = ProdVerifier::default();
+    let trusted_height: u32 = trusted_light_block.height().value().try_into()?;
+    let now = Time::now();
+    let verifies = |candidate: &LightBlock| match verifier.verify_update_header(
+        candidate.as_untrusted_state(),
+        trusted_light_block.as_trusted_state(),
+        options,
+        now,
+    ) {
+        Verdict::Success => Ok(true),
+        Verdict::NotEnoughTrust(_) => Ok(false),
+        Verdict::Invalid(detail) => Err(anyhow::anyhow!(
+            "light block {} is invalid from {trusted_height}: {detail}",
+            candidate.height()
+        )),
+    };
+    anyhow::ensure!(
+        target_height > trusted_height,
+        "the target height {target_height} is not after the trusted height {trusted_height}"
+    );
+
+    let target_light_block = provider.get_light_block(Some(target_height)).await?;
+    if verifies(&target_light_block)? {
+        return Ok(target_light_block);
+    }
+
+    // The light block at `low` verifies, or is the trusted one, and the one at `high` does not.
+    let (mut low, mut high) = (trusted_height, target_height);
+    let mut furthest = None;
+    while high - low > 1 {
+        let pivot = low + (high - low) / 2;
+        let candidate = provider.get_light_block(Some(pivot)).await?;
+        if verifies(&candidate)? {
+            low = pivot;
+            furthest = Some(candidate);
+        } else {
+            high = pivot;
+        }
+    }
+    furthest.ok_or_else(|| {
+        anyhow::anyhow!("no light block after {trusted_height} verifies from it in a single step")
+    })
+}
@@ -0,0 +1,174 @@
+//! A content-addressed cache of proofs, keyed by the verifying key of the program and a hash of
+//! its encoded inputs.
+//!
+//! The timestamp of the [`Env`] given to the update client programs is left out of the key, so
+//! that a request only differing in time reuses a cached proof as long as the proof is still
+//! accepted by the contract. Proofs older than the configured maximum age are ignored.
+
+use std::{
+    borrow::Cow,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use alloy_sol_types::SolValue;
+use sha2::{Digest, Sha256};
+use sp1_ics07_tendermint_solidity::sp1_ics07_tendermint::{
+    Env, UcAndMembershipOutput, UpdateClientOutput,
+};
+use sp1_sdk::{HashableKey, SP1ProofWithPublicValues, SP1VerifyingKey};
+
+use crate::programs::ProgramId;
+
+/// The allowed clock drift of the `SP1ICS07Tendermint` contract in seconds, after which it rejects
+/// a proof as too old.
+pub const ALLOWED_CLOCK_DRIFT: u64 = 3000;
+
+/// The default maximum age of a cached proof in seconds. This leaves some margin below
+/// [`ALLOWED_CLOCK_DRIFT`] for the transaction to be included.
+pub const DEFAULT_MAX_PROOF_AGE: u64 = 2400;
+
+/// The index of the encoded [`Env`] in the stdin of the update client programs.
+/// See [`crate::prover::update_client_stdin`].
+const ENV_STDIN_INDEX: usize = 2;
+
+/// A cache of proofs on the local filesystem.
+#[derive(Clone, Debug)]
+pub struct ProofCache {
+    dir: PathBuf,
+    max_age: Duration,
+}
+
+impl ProofCache {
+    /// Create a cache in `dir`, ignoring the proofs whose [`Env`] is older than `max_age`.
+    ///
+    /// # Errors
+    /// Returns an error if the directory cannot be created.
+    pub fn new(dir: impl Into<PathBuf>, max_age: Duration) -> anyhow::Result<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { dir, max_age })
+    }
+
+    /// Get the cache key of `stdin` for `program`: the hex encoded hash of the inputs, without
+    /// the timestamp of the [`Env`].
+    #[must_use]
+    pub fn key(program: ProgramId, stdin: &[Vec<u8>]) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(program.to_string().as_bytes());
+        for input in normalized_stdin(program, stdin).iter() {
+            hasher.update((input.len() as u64).to_le_bytes());
+            hasher.update(input);
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    /// Get a cached proof of `stdin` for `program` with `vkey`, if any and not stale.
+    #[must_use]
+    pub fn get(
+        &self,
+        program: ProgramId,
+        vkey: &SP1VerifyingKey,
+        stdin: &[Vec<u8>],
+    ) -> Option<SP1ProofWithPublicValues> {
+        let path = self.path(program, vkey, stdin);
+        let bytes = std::fs::read(&path).ok()?;
+        let proof: SP1ProofWithPublicValues = match bincode::deserialize(&bytes) {
+            Ok(proof) => proof,
+            Err(e) => {
+                log::warn!("Removing unreadable cached proof {}: {e}", path.display());
+                let _ = std::fs::remove_file(&path);
+                return None;
+            }
+        };
+
+        if let Some(env_now) = env_now(program, proof.public_values.as_slice()) {
+            let age = unix_now().saturating_sub(env_now);
+            if age > self.max_age.as_secs() {
+                log::debug!(
+                    "Ignoring cached {program} proof {}, it is {age} seconds old",
+                    path.display()
+                );
+                return None;
+            }
+        }
+
+        Some(proof)
+    }
+
+    /// Store a proof of `stdin` for `program` with `vkey`.
+    ///
+    /// # Errors
+    /// Returns an error if the proof cannot be encoded or written.
+    pub fn insert(
+        &self,
+        program: ProgramId,
+        vkey: &SP1VerifyingKey,
+        stdin: &[Vec<u8>],
+        proof: &SP1ProofWithPublicValues,
+    ) -> anyhow::Result<()> {
+        let path = self.path(program, vkey, stdin);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        // Write to a temporary file first, so that readers never see a partial proof.
+        let tmp_path = path.with_extension("tmp");
+        std::fs::write(&tmp_path, bincode::serialize(proof)?)?;
+        std::fs::rename(&tmp_path, &path)?;
+        Ok(())
+    }
+
+    /// Get the directory of the cache.
+    #[must_use]
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, program: ProgramId, vkey: &SP1VerifyingKey, stdin: &[Vec<u8>]) -> PathBuf {
+        let vkey = vkey.bytes32();
+        self.dir
+            .join(vkey.trim_start_matches("0x"))
+            .join(format!("{}.bin", Self::key(program, stdin)))
+    }
+}
+
+/// Get `stdin` with the timestamp of the [`Env`] zeroed, for the programs that take one.
+fn normalized_stdin(program: ProgramId, stdin: &[Vec<u8>]) -> Cow<'_, [Vec<u8>]> {
+    match program {
+        ProgramId::UpdateClient | ProgramId::UpdateClientAndMembership => {
+            let Some(Ok(mut env)) = stdin
+                .get(ENV_STDIN_INDEX)
+                .map(|input| bincode::deserialize::<Env>(input))
+            else {
+                return Cow::Borrowed(stdin);
+            };
+            env.now = 0;
+
+            let mut normalized = stdin.to_vec();
+            normalized[ENV_STDIN_INDEX] = bincode::serialize(&env).expect("failed to encode env");
+            Cow::Owned(normalized)
+        }
+        ProgramId::Membership => Cow::Borrowed(stdin),
+    }
+}
+
+/// Get the timestamp of the [`Env`] in the public values of `program`, if it has one.
+fn env_now(program: ProgramId, public_values: &[u8]) -> Option<u64> {
+    match program {
+        ProgramId::UpdateClient => UpdateClientOutput::abi_decode(public_values, false)
+            .ok()
+            .map(|output| output.env.now),
+        ProgramId::UpdateClientAndMembership => {
+            UcAndMembershipOutput::abi_decode(public_values, false)
+                .ok()
+                .map(|output| output.updateClientOutput.env.now)
+        }
+        ProgramId::Membership => None,
+    }
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
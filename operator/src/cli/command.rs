@@ -1,9 +1,10 @@
 //! Contains the command line interface for the application.
 
-use std::convert::Infallible;
+use std::{convert::Infallible, path::PathBuf};
 
 use clap::{command, Parser};
 use tendermint_light_client_verifier::types::TrustThreshold;
+use tendermint_rpc::client::CompatMode;
 
 /// The command line interface for the operator.
 #[derive(Clone, Debug, Parser)]
@@ -23,6 +24,21 @@ pub enum Commands {
     Genesis(genesis::Args),
     /// The subcommand to produce the fixtures for testing.
     Fixtures(fixtures::Cmd),
+    /// The subcommand to print the verifying keys of the programs.
+    Vkeys(vkeys::Args),
+    /// The subcommand to create, prove and submit proof jobs.
+    Job(job::Cmd),
+    /// The subcommand to run a coordinator that distributes proof jobs to workers.
+    Coordinator(coordinator::Args),
+    /// The subcommand to run a worker that proves the jobs of a coordinator.
+    Worker(worker::Args),
+    /// The subcommand to verify a fixture, update client message or proof job result offline.
+    Verify(verify::Args),
+    /// The subcommand to save a light block to a file, for offline use.
+    LightBlock(light_block::Args),
+    /// The subcommand to analyze the validator set overlap between two blocks, to diagnose
+    /// updates failing the trust threshold.
+    Analyze(analyze::Args),
 }
 
 /// The trust options for client operations.
@@ -40,6 +56,241 @@ pub struct TrustOptions {
     /// Trusting period. [default: 2/3 of unbonding period]
     #[clap(long)]
     pub trusting_period: Option<u32>,
+
+    /// Unbonding period in seconds, instead of querying it from the chain.
+    #[clap(long, conflicts_with_all = ["staking_rest_url", "ics_consumer"])]
+    pub unbonding_period: Option<u32>,
+
+    /// The gRPC-gateway (REST) endpoint to query the staking parameters from, for chains that do
+    /// not answer the staking query over ABCI.
+    #[clap(long, conflicts_with = "ics_consumer")]
+    pub staking_rest_url: Option<String>,
+
+    /// Query the unbonding period from the Interchain Security consumer parameters, for consumer
+    /// chains.
+    #[clap(long)]
+    pub ics_consumer: bool,
+}
+
+impl TrustOptions {
+    /// Get the source of the unbonding period selected by the options.
+    #[must_use]
+    pub fn unbonding_period_source(&self) -> crate::unbonding::UnbondingPeriodSource {
+        use crate::unbonding::UnbondingPeriodSource;
+
+        if let Some(unbonding_period) = self.unbonding_period {
+            UnbondingPeriodSource::Explicit(unbonding_period)
+        } else if let Some(url) = &self.staking_rest_url {
+            UnbondingPeriodSource::Rest(url.clone())
+        } else if self.ics_consumer {
+            UnbondingPeriodSource::ConsumerParams
+        } else {
+            UnbondingPeriodSource::StakingParams
+        }
+    }
+}
+
+/// The options for selecting the SP1 programs.
+#[derive(Clone, Debug, Parser)]
+pub struct ProgramOptions {
+    /// Path to a directory of program versions, or to a TOML manifest of program versions.
+    /// The programs embedded in the binary are always available as the `embedded` version.
+    #[clap(long, env = "SP1_PROGRAMS")]
+    pub programs: Option<PathBuf>,
+
+    /// The program version to use.
+    #[clap(long, env = "SP1_PROGRAM_VERSION")]
+    pub program_version: Option<String>,
+}
+
+/// The options for caching proofs.
+#[derive(Clone, Debug, Parser)]
+pub struct ProofCacheOptions {
+    /// Directory of the proof cache. Proofs of identical inputs are reused instead of proven
+    /// again.
+    #[clap(long, env = "SP1_PROOF_CACHE")]
+    pub proof_cache: Option<PathBuf>,
+
+    /// Seconds after which a cached update client proof is too old to be submitted. Must be
+    /// below the allowed clock drift of the contract.
+    #[clap(
+        long,
+        default_value_t = crate::cache::DEFAULT_MAX_PROOF_AGE,
+        value_parser = parse_max_proof_age
+    )]
+    pub proof_cache_max_age: u64,
+}
+
+impl ProofCacheOptions {
+    /// Open the proof cache, if a directory is given.
+    ///
+    /// # Errors
+    /// Returns an error if the cache directory cannot be created.
+    pub fn open(&self) -> anyhow::Result<Option<crate::cache::ProofCache>> {
+        self.proof_cache
+            .as_deref()
+            .map(|dir| {
+                crate::cache::ProofCache::new(
+                    dir,
+                    std::time::Duration::from_secs(self.proof_cache_max_age),
+                )
+            })
+            .transpose()
+    }
+}
+
+/// The options for proving with the workers of a coordinator instead of locally.
+#[derive(Clone, Debug, Parser)]
+pub struct RemoteProverOptions {
+    /// Prove with the workers of the coordinator at this URL instead of locally.
+    #[clap(long, env = "COORDINATOR_URL")]
+    pub coordinator: Option<String>,
+
+    /// The token of the coordinator, if it requires one.
+    #[clap(long, env = "COORDINATOR_TOKEN", hide_env_values = true)]
+    pub coordinator_token: Option<String>,
+
+    /// Seconds between two polls of the coordinator for the status of a submitted job.
+    #[clap(long, default_value_t = 10)]
+    pub coordinator_poll_interval: u64,
+}
+
+impl RemoteProverOptions {
+    /// Create the client of the coordinator, if one is given.
+    ///
+    /// # Errors
+    /// Returns an error if the URL or the token is invalid.
+    pub fn client(&self) -> anyhow::Result<Option<crate::remote::client::CoordinatorClient>> {
+        self.coordinator
+            .as_deref()
+            .map(|url| {
+                crate::remote::client::CoordinatorClient::new(
+                    url,
+                    self.coordinator_token.as_deref(),
+                )
+            })
+            .transpose()
+    }
+
+    /// Get the interval between two polls of the coordinator.
+    #[must_use]
+    pub const fn poll_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.coordinator_poll_interval)
+    }
+}
+
+/// The options deciding when the operator updates a client.
+#[derive(Clone, Debug, Parser)]
+pub struct TriggerOptions {
+    /// Update when the chain is more than this number of blocks ahead of the client.
+    #[clap(long)]
+    pub max_height_lag: Option<u32>,
+
+    /// Update when the latest consensus state of the client is older than this fraction of the
+    /// trusting period, e.g. '0.5'. Must be in (0, 1].
+    #[clap(long, value_parser = parse_trusting_period_fraction)]
+    pub trusting_period_fraction: Option<f64>,
+
+    /// Update only when requested through `--request-listen`, ignoring the other triggers.
+    #[clap(long)]
+    pub on_request: bool,
+
+    /// The minimum number of seconds between two updates, unless requested.
+    #[clap(long)]
+    pub min_update_interval: Option<u64>,
+
+    /// The maximum number of blocks the client is updated by at once.
+    #[clap(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_height_jump: Option<u32>,
+}
+
+impl TriggerOptions {
+    /// Get the trigger policy of the options. Without any trigger, the client is updated to
+    /// every new block.
+    #[must_use]
+    pub fn policy(&self) -> crate::trigger::TriggerPolicy {
+        crate::trigger::TriggerPolicy {
+            max_height_lag: self.max_height_lag,
+            trusting_period_fraction: self.trusting_period_fraction,
+            on_request: self.on_request,
+            min_interval: self.min_update_interval.map(std::time::Duration::from_secs),
+            max_height_jump: self.max_height_jump,
+        }
+    }
+}
+
+/// The options of the watchdog of the trusting period, as fractions of the trusting period left.
+#[derive(Clone, Debug, Parser)]
+pub struct WatchdogOptions {
+    /// Force an update, whatever the triggers, when less than this fraction is left.
+    #[clap(long, default_value_t = 0.5)]
+    pub expiry_force_update: f64,
+
+    /// Log warnings when less than this fraction is left.
+    #[clap(long, default_value_t = 0.25)]
+    pub expiry_warning: f64,
+
+    /// Log errors when less than this fraction is left, and exit with code 2 if the update
+    /// fails.
+    #[clap(long, default_value_t = 0.1)]
+    pub expiry_critical: f64,
+}
+
+impl WatchdogOptions {
+    /// Get the expiry thresholds of the options.
+    ///
+    /// # Errors
+    /// Returns an error unless `0 < critical < warning < force update <= 1`.
+    pub fn thresholds(&self) -> anyhow::Result<crate::watchdog::ExpiryThresholds> {
+        anyhow::ensure!(
+            0.0 < self.expiry_critical
+                && self.expiry_critical < self.expiry_warning
+                && self.expiry_warning < self.expiry_force_update
+                && self.expiry_force_update <= 1.0,
+            "the expiry thresholds must satisfy 0 < --expiry-critical < --expiry-warning < \
+             --expiry-force-update <= 1"
+        );
+        Ok(crate::watchdog::ExpiryThresholds {
+            force_update: self.expiry_force_update,
+            warning: self.expiry_warning,
+            critical: self.expiry_critical,
+        })
+    }
+}
+
+/// The options for reading the chain from files instead of the Tendermint RPC.
+#[derive(Clone, Debug, Parser)]
+pub struct OfflineOptions {
+    /// A light block file saved with `light-block`, to read from instead of the Tendermint RPC.
+    /// Can be repeated.
+    #[clap(long = "light-block-file", conflicts_with = "synthetic_chain")]
+    pub light_block_files: Vec<PathBuf>,
+
+    /// A TOML specification of a synthetic chain to generate and read from instead of the
+    /// Tendermint RPC.
+    #[clap(long)]
+    pub synthetic_chain: Option<PathBuf>,
+}
+
+impl OfflineOptions {
+    /// Create a provider serving the light block files or the synthetic chain, if any is given.
+    ///
+    /// # Errors
+    /// Returns an error if a light block file or the chain specification cannot be read, or if
+    /// the synthetic chain cannot be generated.
+    pub fn provider(&self) -> anyhow::Result<Option<Box<dyn crate::rpc::LightBlockProvider>>> {
+        if let Some(path) = &self.synthetic_chain {
+            let spec: sp1_ics07_tendermint_testgen::ChainSpec =
+                toml::from_str(&std::fs::read_to_string(path)?)?;
+            return Ok(Some(Box::new(spec.build()?)));
+        }
+        if self.light_block_files.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Box::new(
+            crate::rpc::InMemoryProvider::from_light_block_files(&self.light_block_files)?,
+        )))
+    }
 }
 
 /// The output path for files.
@@ -66,9 +317,128 @@ pub mod genesis {
         #[clap(long, short = 'o', value_parser = super::parse_output_path, default_value = "-")]
         pub output_path: super::OutputPath,
 
+        /// Overwrite an existing genesis file whose verifying keys differ from the programs.
+        #[clap(long)]
+        pub allow_vkey_change: bool,
+
         /// Trust options
         #[clap(flatten)]
         pub trust_options: super::TrustOptions,
+
+        /// Program options. [default version: embedded]
+        #[clap(flatten)]
+        pub program_options: super::ProgramOptions,
+
+        /// Offline options.
+        #[clap(flatten)]
+        pub offline_options: super::OfflineOptions,
+    }
+}
+
+/// The cli interface for the vkeys command.
+pub mod vkeys {
+    use super::{Parser, PathBuf};
+
+    /// The arguments for the `vkeys` command.
+    #[derive(Parser, Debug, Clone)]
+    pub struct Args {
+        /// Genesis file to cross-check the verifying keys against.
+        #[clap(long, default_value = "contracts/script/genesis.json")]
+        pub genesis: PathBuf,
+
+        /// Program options. [default version: embedded]
+        #[clap(flatten)]
+        pub program_options: super::ProgramOptions,
+    }
+}
+
+/// The cli interface for the verify command.
+pub mod verify {
+    use super::Parser;
+
+    /// The arguments for the `verify` command.
+    #[derive(Parser, Debug, Clone)]
+    pub struct Args {
+        /// Path to a fixture or a proof job result, or a hex encoded `MsgUpdateClient` (or a path
+        /// to a file containing one).
+        pub input: String,
+
+        /// Program options. [default version: matching the proof's verifying key]
+        #[clap(flatten)]
+        pub program_options: super::ProgramOptions,
+    }
+}
+
+/// The cli interface for the light block command.
+pub mod light_block {
+    use super::Parser;
+
+    /// The arguments for the `light-block` command.
+    #[derive(Parser, Debug, Clone)]
+    pub struct Args {
+        /// Block height. [default: latest]
+        #[clap(long)]
+        pub height: Option<u32>,
+
+        /// Key paths to save the membership proofs of, queried at the previous height as the
+        /// membership fixtures and jobs do.
+        #[clap(long, value_delimiter = ',')]
+        pub key_paths: Vec<String>,
+
+        /// Light block path. If not provided, the output will be written to stdout.
+        #[clap(long, short = 'o', value_parser = super::parse_output_path, default_value = "-")]
+        pub output_path: super::OutputPath,
+    }
+}
+
+/// The cli interface for the analyze command.
+pub mod analyze {
+    use super::{Parser, TrustThreshold};
+
+    /// The arguments for the `analyze` command.
+    #[derive(Parser, Debug, Clone)]
+    pub struct Args {
+        /// Trusted block height.
+        #[clap(long)]
+        pub trusted_block: u32,
+
+        /// Target block height.
+        #[clap(long)]
+        pub target_block: u32,
+
+        /// Trust level of the client, as a fraction, e.g. '2/3'. [default: the trust level of
+        /// the contract at `CONTRACT_ADDRESS`]
+        #[clap(long, value_parser = super::parse_trust_threshold)]
+        pub trust_level: Option<TrustThreshold>,
+
+        /// Trusting period of the client in seconds. [default: the trusting period of the
+        /// contract at `CONTRACT_ADDRESS`]
+        #[clap(long)]
+        pub trusting_period: Option<u32>,
+
+        /// Offline options.
+        #[clap(flatten)]
+        pub offline_options: super::OfflineOptions,
+    }
+}
+
+/// The cli interface for the mock RPC server.
+pub mod mock_rpc {
+    use super::Parser;
+    use std::net::SocketAddr;
+
+    /// Serves stored light blocks or a synthetic chain over the subset of the CometBFT RPC used
+    /// by the operator, so the operator can be run end to end against localhost.
+    #[derive(Parser, Debug, Clone)]
+    #[command(name = "mock-rpc", version, about, long_about = None)]
+    pub struct Args {
+        /// The address to listen on.
+        #[clap(long, default_value = "127.0.0.1:26657")]
+        pub listen: SocketAddr,
+
+        /// The light block files or synthetic chain to serve.
+        #[clap(flatten)]
+        pub offline_options: super::OfflineOptions,
     }
 }
 
@@ -82,6 +452,130 @@ pub mod operator {
         /// Run update-client only once and then exit.
         #[clap(long)]
         pub only_once: bool,
+
+        /// Program options. [default version: matching the contract's verifying keys]
+        #[clap(flatten)]
+        pub program_options: super::ProgramOptions,
+
+        /// Remote prover options.
+        #[clap(flatten)]
+        pub remote_options: super::RemoteProverOptions,
+
+        /// Proof cache options.
+        #[clap(flatten)]
+        pub cache_options: super::ProofCacheOptions,
+
+        /// The directory the misbehaviour evidence is written to when a witness detects a fork.
+        #[clap(long, default_value = ".")]
+        pub evidence_dir: std::path::PathBuf,
+
+        /// The maximum number of intermediate updates found by bisection when the trust threshold
+        /// is not met between the trusted and the latest height.
+        #[clap(long, default_value_t = 16)]
+        pub max_updates: usize,
+
+        /// Submit only the update to the furthest height verifiable from the trusted height in a
+        /// single step, found by binary search up to the target height, instead of the whole
+        /// sequence of updates to the target height.
+        #[clap(long)]
+        pub furthest_only: bool,
+
+        /// The websocket endpoint to subscribe to new blocks on, for example
+        /// `ws://localhost:26657/websocket`. Without it, the operator polls.
+        #[clap(long, env = "TENDERMINT_WS_URL")]
+        pub websocket_url: Option<String>,
+
+        /// The CometBFT version whose RPC dialect the websocket endpoint speaks, e.g. '0.34' or
+        /// '0.38', instead of the one detected from the status of the node.
+        #[clap(long, env = "TENDERMINT_RPC_COMPAT_MODE", value_parser = super::parse_compat_mode)]
+        pub rpc_compat_mode: Option<super::CompatMode>,
+
+        /// Seconds between updates when polling, and the longest wait for a new block when
+        /// subscribed.
+        #[clap(long, default_value_t = 60)]
+        pub poll_interval: u64,
+
+        /// Trigger options.
+        #[clap(flatten)]
+        pub trigger_options: super::TriggerOptions,
+
+        /// Listen for update requests on this address: `POST /update/<client>` requests an
+        /// update of a client, and `POST /update` of every client.
+        #[clap(long)]
+        pub request_listen: Option<std::net::SocketAddr>,
+
+        /// Watchdog options.
+        #[clap(flatten)]
+        pub watchdog_options: super::WatchdogOptions,
+
+        /// Serve the trusting period metrics of the clients on `/metrics` at this address.
+        #[clap(long)]
+        pub metrics_listen: Option<std::net::SocketAddr>,
+
+        /// A TOML or YAML file of the clients to drive concurrently, instead of the single
+        /// client in the environment variables. The other arguments apply to every client,
+        /// unless overridden by its policy, except the websocket endpoint which is per client.
+        #[clap(long, env = "OPERATOR_CONFIG")]
+        pub config: Option<std::path::PathBuf>,
+    }
+}
+
+/// The cli interface for the coordinator.
+pub mod coordinator {
+    use super::Parser;
+    use std::net::SocketAddr;
+
+    /// The arguments for the `coordinator` command.
+    #[derive(Parser, Debug, Clone)]
+    pub struct Args {
+        /// The address to listen on.
+        #[clap(long, default_value = "127.0.0.1:8080")]
+        pub listen: SocketAddr,
+
+        /// Seconds after which a job assigned to an unresponsive worker is reassigned.
+        #[clap(long, default_value_t = 3600)]
+        pub job_timeout: u64,
+
+        /// Seconds after which a finished job and its result are dropped.
+        #[clap(long, default_value_t = 3600)]
+        pub result_ttl: u64,
+
+        /// Require the workers and the operators to send this token.
+        #[clap(long, env = "COORDINATOR_TOKEN", hide_env_values = true)]
+        pub token: Option<String>,
+    }
+}
+
+/// The cli interface for the worker.
+pub mod worker {
+    use super::Parser;
+
+    /// The arguments for the `worker` command.
+    #[derive(Parser, Debug, Clone)]
+    pub struct Args {
+        /// The URL of the coordinator.
+        #[clap(long, env = "COORDINATOR_URL")]
+        pub coordinator: String,
+
+        /// The token of the coordinator, if it requires one.
+        #[clap(long, env = "COORDINATOR_TOKEN", hide_env_values = true)]
+        pub token: Option<String>,
+
+        /// The name of the worker. [default: `worker-<process id>`]
+        #[clap(long)]
+        pub name: Option<String>,
+
+        /// Seconds to wait before polling the coordinator again when no job is available.
+        #[clap(long, default_value_t = 5)]
+        pub poll_interval: u64,
+
+        /// Program options. [default version: matching each job's verifying key]
+        #[clap(flatten)]
+        pub program_options: super::ProgramOptions,
+
+        /// Proof cache options.
+        #[clap(flatten)]
+        pub cache_options: super::ProofCacheOptions,
     }
 }
 
@@ -128,6 +622,10 @@ pub mod fixtures {
         /// Trust options
         #[clap(flatten)]
         pub trust_options: super::TrustOptions,
+
+        /// Offline options.
+        #[clap(flatten)]
+        pub offline_options: super::OfflineOptions,
     }
 
     /// The arguments for the `Membership` fixture executable.
@@ -149,6 +647,10 @@ pub mod fixtures {
         /// Trust options
         #[clap(flatten)]
         pub trust_options: super::TrustOptions,
+
+        /// Offline options.
+        #[clap(flatten)]
+        pub offline_options: super::OfflineOptions,
     }
 
     /// The arguments for the `UpdateClientAndMembership` fixture executable.
@@ -174,6 +676,117 @@ pub mod fixtures {
         /// Trust options
         #[clap(flatten)]
         pub trust_options: super::TrustOptions,
+
+        /// Offline options.
+        #[clap(flatten)]
+        pub offline_options: super::OfflineOptions,
+    }
+}
+
+/// The cli interface for proof jobs.
+pub mod job {
+    use super::{command, Parser, PathBuf};
+
+    /// The cli interface for proof jobs.
+    #[derive(Clone, Debug, Parser)]
+    #[command(about = "Create, prove and submit proof jobs")]
+    pub struct Cmd {
+        /// The subcommand to run.
+        #[command(subcommand)]
+        pub command: Cmds,
+    }
+
+    /// The subcommands for proof jobs.
+    #[derive(Clone, Debug, Parser)]
+    pub enum Cmds {
+        /// The subcommand to create an update client job for the contract.
+        UpdateClient(UpdateClientCmd),
+        /// The subcommand to create a verify (non)membership job.
+        Membership(MembershipCmd),
+        /// The subcommand to prove a job.
+        Prove(ProveCmd),
+        /// The subcommand to submit a proven update client job to the contract.
+        Submit(SubmitCmd),
+    }
+
+    /// The arguments for the `UpdateClient` job command.
+    #[derive(Parser, Debug, Clone)]
+    #[command(about = "Create an update client job from the contract's latest height")]
+    pub struct UpdateClientCmd {
+        /// Target block. [default: latest]
+        #[clap(long)]
+        pub target_block: Option<u32>,
+
+        /// Job path. If not provided, the output will be written to stdout.
+        #[clap(long, short = 'o', value_parser = super::parse_output_path, default_value = "-")]
+        pub output_path: super::OutputPath,
+
+        /// Program options. [default version: matching the contract's verifying keys]
+        #[clap(flatten)]
+        pub program_options: super::ProgramOptions,
+
+        /// Offline options.
+        #[clap(flatten)]
+        pub offline_options: super::OfflineOptions,
+    }
+
+    /// The arguments for the `Membership` job command.
+    #[derive(Parser, Debug, Clone)]
+    #[command(about = "Create a verify (non)membership job")]
+    pub struct MembershipCmd {
+        /// Trusted block.
+        #[clap(long)]
+        pub trusted_block: u32,
+
+        /// Key paths to prove membership.
+        #[clap(long, value_delimiter = ',')]
+        pub key_paths: Vec<String>,
+
+        /// Job path. If not provided, the output will be written to stdout.
+        #[clap(long, short = 'o', value_parser = super::parse_output_path, default_value = "-")]
+        pub output_path: super::OutputPath,
+
+        /// Program options. [default version: embedded]
+        #[clap(flatten)]
+        pub program_options: super::ProgramOptions,
+
+        /// Offline options.
+        #[clap(flatten)]
+        pub offline_options: super::OfflineOptions,
+    }
+
+    /// The arguments for the `Prove` job command.
+    #[derive(Parser, Debug, Clone)]
+    #[command(about = "Prove a job")]
+    pub struct ProveCmd {
+        /// Job path.
+        #[clap(long)]
+        pub job: PathBuf,
+
+        /// Result path. If not provided, the output will be written to stdout.
+        #[clap(long, short = 'o', value_parser = super::parse_output_path, default_value = "-")]
+        pub output_path: super::OutputPath,
+
+        /// Program options. [default version: matching the job's verifying key]
+        #[clap(flatten)]
+        pub program_options: super::ProgramOptions,
+
+        /// Remote prover options.
+        #[clap(flatten)]
+        pub remote_options: super::RemoteProverOptions,
+
+        /// Proof cache options.
+        #[clap(flatten)]
+        pub cache_options: super::ProofCacheOptions,
+    }
+
+    /// The arguments for the `Submit` job command.
+    #[derive(Parser, Debug, Clone)]
+    #[command(about = "Submit a proven update client job to the contract")]
+    pub struct SubmitCmd {
+        /// Result path.
+        #[clap(long)]
+        pub result: PathBuf,
     }
 }
 
@@ -186,6 +799,24 @@ fn parse_output_path(path: &str) -> Result<OutputPath, Infallible> {
     }
 }
 
+fn parse_compat_mode(input: &str) -> anyhow::Result<CompatMode> {
+    crate::rpc::compat_mode_from_version(input)
+}
+
+fn parse_max_proof_age(input: &str) -> anyhow::Result<u64> {
+    let max_age: u64 = input.parse()?;
+    anyhow::ensure!(
+        max_age < crate::cache::ALLOWED_CLOCK_DRIFT,
+        "the maximum proof age must be below the allowed clock drift of the contract ({}s)",
+        crate::cache::ALLOWED_CLOCK_DRIFT
+    );
+    Ok(max_age)
+}
+
+fn parse_trusting_period_fraction(input: &str) -> anyhow::Result<f64> {
+    crate::trigger::check_trusting_period_fraction(input.parse()?)
+}
+
 fn parse_trust_threshold(input: &str) -> anyhow::Result<TrustThreshold> {
     let (num_part, denom_part) = input.split_once('/').ok_or_else(|| {
         anyhow::anyhow!("invalid trust threshold fraction: expected format 'numerator/denominator'")
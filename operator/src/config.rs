@@ -0,0 +1,238 @@
+//! The configuration file of an operator driving multiple light client contracts.
+
+use std::{path::Path, time::Duration};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use tendermint_rpc::client::CompatMode;
+
+use crate::{
+    rpc::{
+        compat_mode_from_version, CachingProvider, FailoverProvider, HttpProvider, Selection,
+        TransportOptions, WitnessedProvider,
+    },
+    trigger::check_trusting_period_fraction,
+};
+
+/// The configuration of an operator driving multiple clients, read from a TOML or YAML file:
+///
+/// ```toml
+/// [[clients]]
+/// name = "celestia-sepolia"
+/// tendermint_rpc_url = "https://rpc.celestia-mocha.com/"
+/// eth_rpc_url = "https://ethereum-sepolia.publicnode.com/"
+/// contract_address = "0x..."
+/// private_key_env = "SEPOLIA_PRIVATE_KEY"
+///
+/// [clients.transport]
+/// timeout = 10
+/// headers_env = "SEPOLIA_RPC_HEADERS"
+///
+/// [clients.policy]
+/// poll_interval = 30
+/// max_height_lag = 1000
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OperatorConfig {
+    /// The clients driven by the operator.
+    pub clients: Vec<ClientConfig>,
+}
+
+/// A light client contract driven by the operator, and the chains it connects.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClientConfig {
+    /// The name of the client in the logs.
+    pub name: String,
+    /// The Tendermint RPC endpoints, as a comma separated list to fail over between.
+    pub tendermint_rpc_url: String,
+    /// The order in which the Tendermint RPC endpoints are tried: `priority` or `round-robin`.
+    #[serde(default)]
+    pub tendermint_rpc_selection: Option<String>,
+    /// The Tendermint RPC endpoints the headers are cross-checked against.
+    #[serde(default)]
+    pub witness_urls: Vec<String>,
+    /// The websocket endpoint to subscribe to new blocks on, instead of polling.
+    #[serde(default)]
+    pub websocket_url: Option<String>,
+    /// The URL of the EVM RPC node.
+    pub eth_rpc_url: String,
+    /// The address of the `SP1ICS07Tendermint` contract.
+    pub contract_address: String,
+    /// The environment variable holding the private key that signs the transactions, so that
+    /// the key is not stored in the configuration file.
+    #[serde(default = "default_private_key_env")]
+    pub private_key_env: String,
+    /// The transport of the Tendermint RPC endpoints of the client.
+    #[serde(default)]
+    pub transport: TransportConfig,
+    /// The update policy of the client. Unset values default to the command line arguments.
+    #[serde(default)]
+    pub policy: PolicyConfig,
+}
+
+/// The transport of the Tendermint RPC endpoints of a client. Unset values default to the
+/// defaults of [`TransportOptions`], not to the `TENDERMINT_RPC_*` environment variables, and
+/// the witnesses always use the defaults.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransportConfig {
+    /// The timeout of a request in seconds.
+    pub timeout: Option<u64>,
+    /// The number of retries of a request that failed with a transient error.
+    pub retries: Option<u32>,
+    /// The maximum number of concurrent requests per endpoint.
+    pub max_concurrency: Option<usize>,
+    /// The environment variable holding comma separated `Name: value` headers added to every
+    /// request, such as an API key, so that credentials are not stored in the configuration
+    /// file. Credentials in the URLs are sent as basic auth.
+    pub headers_env: Option<String>,
+    /// The CometBFT version whose RPC dialect the websocket subscription uses, e.g. '0.34',
+    /// instead of the one detected from the status of the node.
+    pub compat_mode: Option<String>,
+}
+
+/// The update policy of a client, overriding the command line arguments of the operator.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyConfig {
+    /// Seconds between updates when polling.
+    pub poll_interval: Option<u64>,
+    /// The maximum number of intermediate updates found by bisection.
+    pub max_updates: Option<usize>,
+    /// Submit only the update to the furthest height verifiable from the trusted height in a
+    /// single step.
+    pub furthest_only: Option<bool>,
+    /// Update when the chain is more than this number of blocks ahead of the client.
+    pub max_height_lag: Option<u32>,
+    /// Update when the latest consensus state is older than this fraction of the trusting period.
+    pub trusting_period_fraction: Option<f64>,
+    /// Update only on request.
+    pub on_request: Option<bool>,
+    /// The minimum number of seconds between two updates, unless requested.
+    pub min_update_interval: Option<u64>,
+    /// The maximum number of blocks the client is updated by at once.
+    pub max_height_jump: Option<u32>,
+}
+
+fn default_private_key_env() -> String {
+    "PRIVATE_KEY".to_string()
+}
+
+impl TransportConfig {
+    /// Gets the transport options of the endpoints.
+    ///
+    /// # Errors
+    /// Returns an error if the headers environment variable is not set or invalid.
+    pub fn options(&self) -> Result<TransportOptions> {
+        let mut options = TransportOptions::default();
+        if let Some(timeout) = self.timeout {
+            options.timeout = Duration::from_secs(timeout);
+        }
+        if let Some(retries) = self.retries {
+            options.max_retries = retries;
+        }
+        if let Some(max_concurrency) = self.max_concurrency {
+            options.max_concurrency = max_concurrency;
+        }
+        if let Some(headers_env) = &self.headers_env {
+            let headers =
+                std::env::var(headers_env).with_context(|| format!("{headers_env} not set"))?;
+            options = options
+                .with_headers(&headers)
+                .with_context(|| format!("invalid {headers_env}"))?;
+        }
+        Ok(options)
+    }
+
+    /// Gets the compatibility mode of the websocket subscription, if it is forced.
+    ///
+    /// # Errors
+    /// Returns an error if the version is not supported.
+    pub fn compat_mode(&self) -> Result<Option<CompatMode>> {
+        self.compat_mode
+            .as_deref()
+            .map(compat_mode_from_version)
+            .transpose()
+    }
+}
+
+impl OperatorConfig {
+    /// Reads the configuration from a YAML file if its extension is `.yaml` or `.yml`, and from
+    /// a TOML file otherwise.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or parsed, if it has no clients, if two
+    /// clients have the same name, or if the options of a client are invalid.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let config: Self = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("yaml" | "yml") => serde_yaml::from_str(&contents)?,
+            _ => toml::from_str(&contents)?,
+        };
+
+        anyhow::ensure!(!config.clients.is_empty(), "no clients configured");
+        for (i, client) in config.clients.iter().enumerate() {
+            anyhow::ensure!(
+                config.clients[..i].iter().all(|c| c.name != client.name),
+                "duplicate client name '{}'",
+                client.name
+            );
+            client
+                .transport
+                .compat_mode()
+                .with_context(|| format!("invalid compat_mode of client '{}'", client.name))?;
+            if let Some(fraction) = client.policy.trusting_period_fraction {
+                check_trusting_period_fraction(fraction).with_context(|| {
+                    format!(
+                        "invalid trusting_period_fraction of client '{}'",
+                        client.name
+                    )
+                })?;
+            }
+        }
+        Ok(config)
+    }
+}
+
+impl ClientConfig {
+    /// Creates the light block provider of the client, with the same layers as
+    /// [`CachingProvider::from_env`] except the cassette. The endpoints use the transport of the
+    /// client, and the witnesses the default transport.
+    ///
+    /// # Errors
+    /// Returns an error if a URL, the selection or the transport is invalid.
+    pub fn provider(
+        &self,
+    ) -> Result<CachingProvider<WitnessedProvider<FailoverProvider<HttpProvider>>>> {
+        let selection: Selection = self
+            .tendermint_rpc_selection
+            .as_deref()
+            .map(str::parse)
+            .transpose()?
+            .unwrap_or_default();
+        let options = self.transport.options()?;
+        let endpoints = self
+            .tendermint_rpc_url
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(|url| {
+                let provider = HttpProvider::with_transport_options(url, options.clone())?;
+                Ok((url.to_string(), provider))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let witnesses = self
+            .witness_urls
+            .iter()
+            .map(|url| Ok((url.clone(), HttpProvider::new(url)?)))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(CachingProvider::new(WitnessedProvider::new(
+            FailoverProvider::new(endpoints, selection)?,
+            witnesses,
+        )))
+    }
+}
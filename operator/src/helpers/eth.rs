@@ -11,7 +11,17 @@ use alloy::{network::EthereumWallet, signers::local::PrivateKeySigner};
 /// Panics if the `PRIVATE_KEY` environment variable is not a valid private key.
 #[must_use]
 pub fn wallet_from_env() -> EthereumWallet {
-    let mut private_key = env::var("PRIVATE_KEY").expect("PRIVATE_KEY not set");
+    wallet_from_env_var("PRIVATE_KEY")
+}
+
+/// Create an Ethereum wallet from the private key in the environment variable `name`.
+///
+/// # Panics
+/// Panics if the environment variable is not set.
+/// Panics if the environment variable is not a valid private key.
+#[must_use]
+pub fn wallet_from_env_var(name: &str) -> EthereumWallet {
+    let mut private_key = env::var(name).unwrap_or_else(|_| panic!("{name} not set"));
     if let Some(stripped) = private_key.strip_prefix("0x") {
         private_key = stripped.to_string();
     }
@@ -0,0 +1,213 @@
+//! Proof jobs that capture the inputs of a program so that they can be proven later, possibly on
+//! another machine.
+
+use std::path::Path;
+
+use crate::{
+    cache::ALLOWED_CLOCK_DRIFT,
+    helpers::light_block::LightBlockExt,
+    programs::ProgramId,
+    prover::{membership_stdin, update_client_stdin},
+};
+use ibc_client_tendermint::types::ConsensusState;
+use ibc_core_commitment_types::merkle::MerkleProof;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use sp1_ics07_tendermint_solidity::sp1_ics07_tendermint::{
+    ConsensusState as SolConsensusState, Env, TrustThreshold,
+};
+use sp1_sdk::{SP1ProofWithPublicValues, SP1Stdin};
+use tendermint_light_client_verifier::types::LightBlock;
+
+/// A proof job: everything needed to generate a proof for a program.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(clippy::module_name_repetitions)]
+pub struct ProofJob {
+    /// The program to prove.
+    pub program: ProgramId,
+    /// The encoded verifying key of the program the job was created for.
+    pub vkey: String,
+    /// The encoded inputs written to the program's stdin.
+    #[serde_as(as = "Vec<serde_with::hex::Hex>")]
+    pub stdin: Vec<Vec<u8>>,
+    /// The metadata describing the inputs.
+    pub metadata: ProofJobMetadata,
+}
+
+/// The metadata of a [`ProofJob`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(clippy::module_name_repetitions)]
+pub struct ProofJobMetadata {
+    /// The chain identifier.
+    pub chain_id: String,
+    /// The trusted height, or the proof height for membership jobs.
+    pub trusted_height: u32,
+    /// The target height of update client jobs.
+    pub target_height: Option<u32>,
+    /// The environment of update client jobs.
+    pub env: Option<Env>,
+    /// The key paths of membership jobs.
+    #[serde(default)]
+    pub key_paths: Vec<String>,
+    /// The unix timestamp at which the job was created.
+    pub created_at: u64,
+}
+
+/// A proven [`ProofJob`].
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(clippy::module_name_repetitions)]
+pub struct ProofJobResult {
+    /// The proven job.
+    pub job: ProofJob,
+    /// The bincode encoded [`SP1ProofWithPublicValues`].
+    #[serde_as(as = "serde_with::hex::Hex")]
+    pub proof: Vec<u8>,
+}
+
+impl ProofJob {
+    /// Create an update client job from `trusted_light_block` to `target_light_block`.
+    ///
+    /// # Errors
+    /// Returns an error if the chain identifier or heights cannot be parsed.
+    pub fn update_client(
+        vkey: String,
+        trusted_light_block: &LightBlock,
+        target_light_block: LightBlock,
+        trust_threshold: TrustThreshold,
+        trusting_period: u32,
+    ) -> anyhow::Result<Self> {
+        let trusted_consensus_state: SolConsensusState =
+            trusted_light_block.to_consensus_state().into();
+        let target_height = target_light_block.height().value().try_into()?;
+        let proposed_header = target_light_block.into_header(trusted_light_block);
+
+        let now = unix_now()?;
+        let env = Env {
+            chainId: trusted_light_block.chain_id()?.to_string(),
+            trustThreshold: trust_threshold,
+            trustingPeriod: trusting_period,
+            now,
+        };
+
+        let stdin = update_client_stdin(&trusted_consensus_state, &proposed_header, &env);
+        Ok(Self {
+            program: ProgramId::UpdateClient,
+            vkey,
+            stdin: stdin.buffer,
+            metadata: ProofJobMetadata {
+                chain_id: env.chainId.clone(),
+                trusted_height: trusted_light_block.height().value().try_into()?,
+                target_height: Some(target_height),
+                env: Some(env),
+                key_paths: Vec::new(),
+                created_at: now,
+            },
+        })
+    }
+
+    /// Create a verify (non)membership job on the commitment root of `trusted_light_block`.
+    ///
+    /// # Errors
+    /// Returns an error if the chain identifier or height cannot be parsed.
+    pub fn membership(
+        vkey: String,
+        trusted_light_block: &LightBlock,
+        kv_proofs: Vec<(String, MerkleProof, Vec<u8>)>,
+    ) -> anyhow::Result<Self> {
+        let consensus_state: ConsensusState = trusted_light_block.to_consensus_state();
+        let key_paths = kv_proofs.iter().map(|(path, _, _)| path.clone()).collect();
+
+        let stdin = membership_stdin(consensus_state.root.as_bytes(), kv_proofs);
+        Ok(Self {
+            program: ProgramId::Membership,
+            vkey,
+            stdin: stdin.buffer,
+            metadata: ProofJobMetadata {
+                chain_id: trusted_light_block.chain_id()?.to_string(),
+                trusted_height: trusted_light_block.height().value().try_into()?,
+                target_height: None,
+                env: None,
+                key_paths,
+                created_at: unix_now()?,
+            },
+        })
+    }
+
+    /// Get the [`SP1Stdin`] of the job.
+    #[must_use]
+    pub fn sp1_stdin(&self) -> SP1Stdin {
+        let mut stdin = SP1Stdin::new();
+        for input in &self.stdin {
+            stdin.write_vec(input.clone());
+        }
+        stdin
+    }
+
+    /// Read a job from a JSON file.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or decoded.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    /// Ensures that the time the job was created at, which the update client programs commit to
+    /// as `now`, is less than `max_age` seconds old. The contract rejects proofs whose `now` is
+    /// older than [`ALLOWED_CLOCK_DRIFT`]. Jobs without an [`Env`] have no such limit.
+    ///
+    /// # Errors
+    /// Returns an error if the job is too old.
+    pub fn ensure_fresh(&self, max_age: u64) -> anyhow::Result<()> {
+        let Some(env) = &self.metadata.env else {
+            return Ok(());
+        };
+        let age = unix_now()?.saturating_sub(env.now);
+        anyhow::ensure!(
+            age < max_age,
+            "the job was created {age}s ago, its proof must be submitted within \
+             {ALLOWED_CLOCK_DRIFT}s: create the job again"
+        );
+        Ok(())
+    }
+}
+
+impl ProofJobResult {
+    /// Create a new result for `job`.
+    ///
+    /// # Errors
+    /// Returns an error if the proof cannot be encoded.
+    pub fn new(job: ProofJob, proof: &SP1ProofWithPublicValues) -> anyhow::Result<Self> {
+        Ok(Self {
+            job,
+            proof: bincode::serialize(proof)?,
+        })
+    }
+
+    /// Decode the proof.
+    ///
+    /// # Errors
+    /// Returns an error if the proof cannot be decoded.
+    pub fn sp1_proof(&self) -> anyhow::Result<SP1ProofWithPublicValues> {
+        Ok(bincode::deserialize(&self.proof)?)
+    }
+
+    /// Read a result from a JSON file.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or decoded.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+}
+
+/// Get the current unix timestamp in seconds.
+fn unix_now() -> anyhow::Result<u64> {
+    Ok(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_secs())
+}
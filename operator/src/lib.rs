@@ -1,9 +1,18 @@
 //! The crate that contains the types and utilities for `sp1-ics07-tendermint-operator` executable.
 #![deny(missing_docs, clippy::nursery, clippy::pedantic, warnings)]
 
+pub mod bisection;
+pub mod cache;
 pub mod cli;
+pub mod config;
 pub mod helpers;
+pub mod job;
+pub mod preflight;
 pub mod programs;
 pub mod prover;
-pub(crate) mod rpc;
+pub mod remote;
+pub mod rpc;
 pub mod runners;
+pub mod trigger;
+pub mod unbonding;
+pub mod watchdog;
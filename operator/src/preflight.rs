@@ -0,0 +1,160 @@
+//! Native preflight of the programs, to catch invalid inputs before spending time and fees on
+//! proving them.
+
+use std::{
+    cell::Cell,
+    panic::{self, catch_unwind, AssertUnwindSafe},
+    sync::Once,
+};
+
+use alloy_sol_types::SolValue;
+use ibc_client_tendermint::types::Header;
+use ibc_core_commitment_types::merkle::MerkleProof;
+use ibc_proto::Protobuf;
+use sp1_ics07_tendermint_solidity::sp1_ics07_tendermint::{
+    ConsensusState as SolConsensusState, Env,
+};
+
+use crate::programs::ProgramId;
+
+/// Known failure causes of the programs and a readable explanation for each.
+const HINTS: [(&[&str], &str); 4] = [
+    (
+        &["NotEnoughTrust", "InsufficientSignersOverlap"],
+        "the trusted validators that signed the target commit do not meet the trust threshold, \
+         try a target height closer to the trusted height",
+    ),
+    (
+        &["NotWithinTrustPeriod", "expired"],
+        "the trusted consensus state is outside of the trusting period",
+    ),
+    (
+        &["ChainId", "chain id"],
+        "the chain identifier of the header does not match the client",
+    ),
+    (
+        &[
+            "verify_membership",
+            "verify_non_membership",
+            "VerificationFailure",
+        ],
+        "an ICS-23 proof does not verify against the commitment root",
+    ),
+];
+
+thread_local! {
+    /// Whether the panics of the current thread are caught by a preflight.
+    static IN_PREFLIGHT: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs `program` natively on the `stdin` buffer, decoding the inputs exactly as the program does.
+/// Returns the ABI encoded output, which are the public values of the proof.
+///
+/// This is the embedded version of the program, so the preflight of another version may reject
+/// inputs that it accepts, or the other way around.
+///
+/// # Errors
+/// Returns an error with a readable diagnostic if the inputs cannot be decoded or the program
+/// fails.
+pub fn preflight(program: ProgramId, stdin: &[Vec<u8>]) -> anyhow::Result<Vec<u8>> {
+    let stdin = stdin.to_vec();
+    catch_silently(|| run_program(program, stdin)).map_err(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|s| (*s).to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown error".to_string());
+        let hint = HINTS
+            .iter()
+            .find(|(patterns, _)| patterns.iter().any(|p| message.contains(p)))
+            .map_or_else(String::new, |(_, hint)| format!("\nhint: {hint}"));
+
+        anyhow::anyhow!("preflight of the {program} program failed: {message}{hint}")
+    })
+}
+
+/// Catches the panics of `f` without printing them, since they are reported as errors. The panics
+/// of the other threads are still printed.
+fn catch_silently<T>(f: impl FnOnce() -> T) -> std::thread::Result<T> {
+    static SILENCE_PREFLIGHT_PANICS: Once = Once::new();
+    SILENCE_PREFLIGHT_PANICS.call_once(|| {
+        let hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if !IN_PREFLIGHT.with(Cell::get) {
+                hook(info);
+            }
+        }));
+    });
+
+    IN_PREFLIGHT.with(|in_preflight| in_preflight.set(true));
+    let result = catch_unwind(AssertUnwindSafe(f));
+    IN_PREFLIGHT.with(|in_preflight| in_preflight.set(false));
+    result
+}
+
+/// Decodes the inputs and runs the program, panicking on failure like the zkVM program.
+fn run_program(program: ProgramId, stdin: Vec<Vec<u8>>) -> Vec<u8> {
+    let mut stdin = stdin.into_iter();
+    let mut read_vec = || stdin.next().expect("not enough inputs in stdin");
+
+    match program {
+        ProgramId::UpdateClient => {
+            let (trusted_consensus_state, proposed_header, env) =
+                read_update_client_inputs(&mut read_vec);
+            sp1_ics07_tendermint_update_client::update_client(
+                trusted_consensus_state.into(),
+                proposed_header,
+                env,
+            )
+            .abi_encode()
+        }
+        ProgramId::Membership => {
+            let app_hash: [u8; 32] = read_vec().try_into().expect("invalid app hash");
+            let request_len = read_vec()[0];
+            assert!(request_len != 0, "no key-value pairs to verify");
+            let kv_proofs = read_kv_proofs(&mut read_vec, request_len);
+            sp1_ics07_tendermint_membership::membership(app_hash, kv_proofs.into_iter())
+                .abi_encode()
+        }
+        ProgramId::UpdateClientAndMembership => {
+            let (trusted_consensus_state, proposed_header, env) =
+                read_update_client_inputs(&mut read_vec);
+            let request_len = read_vec()[0];
+            assert!(request_len != 0, "no key-value pairs to verify");
+            let kv_proofs = read_kv_proofs(&mut read_vec, request_len);
+            sp1_ics07_tendermint_uc_and_membership::update_client_and_membership(
+                trusted_consensus_state.into(),
+                proposed_header,
+                env,
+                kv_proofs.into_iter(),
+            )
+            .abi_encode()
+        }
+    }
+}
+
+fn read_update_client_inputs(
+    read_vec: &mut impl FnMut() -> Vec<u8>,
+) -> (SolConsensusState, Header, Env) {
+    let trusted_consensus_state = bincode::deserialize::<SolConsensusState>(&read_vec())
+        .expect("failed to decode the trusted consensus state");
+    let proposed_header = serde_cbor::from_slice::<Header>(&read_vec())
+        .expect("failed to decode the proposed header");
+    let env = bincode::deserialize::<Env>(&read_vec()).expect("failed to decode the env");
+    (trusted_consensus_state, proposed_header, env)
+}
+
+fn read_kv_proofs(
+    read_vec: &mut impl FnMut() -> Vec<u8>,
+    request_len: u8,
+) -> Vec<(String, MerkleProof, Vec<u8>)> {
+    (0..request_len)
+        .map(|_| {
+            let path_str = String::from_utf8(read_vec()).expect("invalid key path");
+            let merkle_proof =
+                MerkleProof::decode_vec(&read_vec()).expect("failed to decode the merkle proof");
+            let value = read_vec();
+            (path_str, merkle_proof, value)
+        })
+        .collect()
+}
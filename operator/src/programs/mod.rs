@@ -0,0 +1,109 @@
+//! Programs for `sp1-ics07-tendermint`.
+
+use std::sync::OnceLock;
+
+use sp1_sdk::{MockProver, Prover, SP1VerifyingKey};
+
+pub mod registry;
+
+/// Identifies one of the SP1 ICS07 Tendermint programs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ProgramId {
+    /// The [`UpdateClientProgram`].
+    UpdateClient,
+    /// The [`MembershipProgram`].
+    Membership,
+    /// The [`UpdateClientAndMembershipProgram`].
+    UpdateClientAndMembership,
+}
+
+/// Trait for SP1 ICS07 Tendermint programs.
+pub trait SP1Program {
+    /// The identifier of the program.
+    const ID: ProgramId;
+
+    /// The ELF file for the program.
+    const ELF: &'static [u8];
+
+    /// Get the verifying key for the program. See [`ProgramId::vkey`].
+    #[must_use]
+    fn get_vkey() -> SP1VerifyingKey {
+        Self::ID.vkey().clone()
+    }
+}
+
+/// SP1 ICS07 Tendermint update client program.
+pub struct UpdateClientProgram;
+
+/// SP1 ICS07 Tendermint verify (non)membership program.
+pub struct MembershipProgram;
+
+/// SP1 ICS07 Tendermint update client and verify (non)membership program.
+pub struct UpdateClientAndMembershipProgram;
+
+impl SP1Program for UpdateClientProgram {
+    const ID: ProgramId = ProgramId::UpdateClient;
+    const ELF: &'static [u8] =
+        include_bytes!("../../elf/update-client-riscv32im-succinct-zkvm-elf");
+}
+
+impl SP1Program for MembershipProgram {
+    const ID: ProgramId = ProgramId::Membership;
+    const ELF: &'static [u8] = include_bytes!("../../elf/membership-riscv32im-succinct-zkvm-elf");
+}
+
+impl SP1Program for UpdateClientAndMembershipProgram {
+    const ID: ProgramId = ProgramId::UpdateClientAndMembership;
+    const ELF: &'static [u8] =
+        include_bytes!("../../elf/uc-and-membership-riscv32im-succinct-zkvm-elf");
+}
+
+impl ProgramId {
+    /// All the programs, in the order their verifying keys are passed to the contract.
+    pub const ALL: [Self; 3] = [
+        Self::UpdateClient,
+        Self::Membership,
+        Self::UpdateClientAndMembership,
+    ];
+
+    /// Get the embedded ELF file for the program.
+    #[must_use]
+    pub const fn elf(self) -> &'static [u8] {
+        match self {
+            Self::UpdateClient => UpdateClientProgram::ELF,
+            Self::Membership => MembershipProgram::ELF,
+            Self::UpdateClientAndMembership => UpdateClientAndMembershipProgram::ELF,
+        }
+    }
+
+    /// Get the verifying key of the embedded program.
+    /// It is computed using [`MockProver`] on the first call and memoized afterwards.
+    #[must_use]
+    pub fn vkey(self) -> &'static SP1VerifyingKey {
+        static VKEYS: [OnceLock<SP1VerifyingKey>; 3] =
+            [OnceLock::new(), OnceLock::new(), OnceLock::new()];
+
+        VKEYS[self as usize].get_or_init(|| {
+            let mock_prover = MockProver::new();
+            let (_, vkey) = mock_prover.setup(self.elf());
+            vkey
+        })
+    }
+
+    /// Get the file name of the program's ELF as produced by `just build-programs`.
+    #[must_use]
+    pub fn elf_file_name(self) -> String {
+        format!("{self}-riscv32im-succinct-zkvm-elf")
+    }
+}
+
+impl std::fmt::Display for ProgramId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UpdateClient => write!(f, "update-client"),
+            Self::Membership => write!(f, "membership"),
+            Self::UpdateClientAndMembership => write!(f, "uc-and-membership"),
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! A versioned registry of the SP1 ICS07 Tendermint programs loaded at runtime.
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
+};
+
+use sp1_sdk::{HashableKey, MockProver, Prover, SP1VerifyingKey};
+
+use super::ProgramId;
+
+/// The version name of the programs embedded in the operator binary.
+pub const EMBEDDED_VERSION: &str = "embedded";
+
+/// The ELF files and verifying keys of one version of the SP1 ICS07 Tendermint programs.
+#[derive(Clone)]
+pub struct ProgramSet {
+    /// The version of the programs.
+    pub version: String,
+    programs: HashMap<ProgramId, (Cow<'static, [u8]>, SP1VerifyingKey)>,
+}
+
+impl ProgramSet {
+    /// Create a program set from the ELF files embedded in the operator binary.
+    #[must_use]
+    pub fn embedded() -> Self {
+        let programs = ProgramId::ALL
+            .into_iter()
+            .map(|program| {
+                (
+                    program,
+                    (Cow::Borrowed(program.elf()), program.vkey().clone()),
+                )
+            })
+            .collect();
+
+        Self {
+            version: EMBEDDED_VERSION.to_string(),
+            programs,
+        }
+    }
+
+    /// Create a program set from the given ELF files, computing their verifying keys.
+    #[must_use]
+    pub fn from_elfs(
+        version: &str,
+        elfs: impl IntoIterator<Item = (ProgramId, Cow<'static, [u8]>)>,
+    ) -> Self {
+        let mock_prover = MockProver::new();
+        let programs = elfs
+            .into_iter()
+            .map(|(program, elf)| {
+                let (_, vkey) = mock_prover.setup(&elf);
+                (program, (elf, vkey))
+            })
+            .collect();
+
+        Self {
+            version: version.to_string(),
+            programs,
+        }
+    }
+
+    /// Load a program set from a directory containing the ELF files named as in `elf/`.
+    ///
+    /// # Errors
+    /// Returns an error if any of the ELF files cannot be read.
+    pub fn load_dir(version: &str, dir: &Path) -> anyhow::Result<Self> {
+        let elfs = ProgramId::ALL
+            .into_iter()
+            .map(|program| {
+                let path = dir.join(program.elf_file_name());
+                let elf = std::fs::read(&path)
+                    .map_err(|e| anyhow::anyhow!("failed to read {}: {e}", path.display()))?;
+                anyhow::Ok((program, Cow::Owned(elf)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self::from_elfs(version, elfs))
+    }
+
+    /// Get the ELF file of a program.
+    #[must_use]
+    pub fn elf(&self, program: ProgramId) -> Option<&[u8]> {
+        self.programs.get(&program).map(|(elf, _)| elf.as_ref())
+    }
+
+    /// Get the verifying key of a program.
+    #[must_use]
+    pub fn vkey(&self, program: ProgramId) -> Option<&SP1VerifyingKey> {
+        self.programs.get(&program).map(|(_, vkey)| vkey)
+    }
+}
+
+/// A registry that maps program versions to their [`ProgramSet`].
+#[derive(Clone, Default)]
+pub struct ProgramRegistry {
+    sets: BTreeMap<String, ProgramSet>,
+}
+
+/// The manifest describing the program versions, e.g.
+///
+/// ```toml
+/// [versions.v1]
+/// update-client = "v1/update-client-riscv32im-succinct-zkvm-elf"
+/// membership = "v1/membership-riscv32im-succinct-zkvm-elf"
+/// uc-and-membership = "v1/uc-and-membership-riscv32im-succinct-zkvm-elf"
+/// ```
+///
+/// Relative paths are resolved against the directory of the manifest.
+#[derive(Debug, serde::Deserialize)]
+struct RegistryManifest {
+    versions: BTreeMap<String, HashMap<ProgramId, PathBuf>>,
+}
+
+impl ProgramRegistry {
+    /// Create a registry containing only the embedded programs.
+    #[must_use]
+    pub fn embedded() -> Self {
+        let mut registry = Self::default();
+        registry.insert(ProgramSet::embedded());
+        registry
+    }
+
+    /// Load a registry from `path`, in addition to the embedded programs.
+    ///
+    /// If `path` is a directory, each of its subdirectories is loaded as a version named after
+    /// the subdirectory. Otherwise, `path` is read as a TOML manifest.
+    ///
+    /// # Errors
+    /// Returns an error if the directory or manifest cannot be read, or if any of the ELF files
+    /// cannot be read.
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let mut registry = Self::embedded();
+
+        if path.is_dir() {
+            for entry in std::fs::read_dir(path)? {
+                let entry = entry?;
+                if !entry.file_type()?.is_dir() {
+                    continue;
+                }
+                let version = entry.file_name().to_string_lossy().into_owned();
+                registry.insert(ProgramSet::load_dir(&version, &entry.path())?);
+            }
+        } else {
+            let manifest: RegistryManifest = toml::from_str(&std::fs::read_to_string(path)?)?;
+            let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+            for (version, paths) in manifest.versions {
+                let elfs = paths
+                    .into_iter()
+                    .map(|(program, elf_path)| {
+                        let elf_path = base_dir.join(elf_path);
+                        let elf = std::fs::read(&elf_path).map_err(|e| {
+                            anyhow::anyhow!("failed to read {}: {e}", elf_path.display())
+                        })?;
+                        anyhow::Ok((program, Cow::Owned(elf)))
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                registry.insert(ProgramSet::from_elfs(&version, elfs));
+            }
+        }
+
+        for set in registry.sets.values() {
+            log::info!(
+                "Loaded programs version '{}' with update client vkey {}",
+                set.version,
+                set.vkey(ProgramId::UpdateClient)
+                    .map_or_else(|| "<none>".to_string(), HashableKey::bytes32)
+            );
+        }
+
+        Ok(registry)
+    }
+
+    /// Load a registry from `path` if given, otherwise create a registry containing only the
+    /// embedded programs. See [`Self::load`].
+    ///
+    /// # Errors
+    /// Returns an error if the registry cannot be loaded from `path`.
+    pub fn load_or_embedded(path: Option<&Path>) -> anyhow::Result<Self> {
+        path.map_or_else(|| Ok(Self::embedded()), Self::load)
+    }
+
+    /// Get the program set of `version`.
+    ///
+    /// # Errors
+    /// Returns an error listing the known versions if `version` is not in the registry.
+    pub fn get_or_err(&self, version: &str) -> anyhow::Result<&ProgramSet> {
+        self.get(version).ok_or_else(|| {
+            anyhow::anyhow!(
+                "unknown program version '{version}', known versions: {}",
+                self.versions().collect::<Vec<_>>().join(", ")
+            )
+        })
+    }
+
+    /// Get the program set of `version` if given, otherwise the program set whose `program`
+    /// verifying key matches `vkey`. See [`Self::find_by_vkey`].
+    ///
+    /// # Errors
+    /// Returns an error if no matching program set is found.
+    pub fn select(
+        &self,
+        version: Option<&str>,
+        program: ProgramId,
+        vkey: &str,
+    ) -> anyhow::Result<&ProgramSet> {
+        version.map_or_else(
+            || {
+                self.find_by_vkey(program, vkey).ok_or_else(|| {
+                    anyhow::anyhow!("no program version matches the {program} vkey {vkey}")
+                })
+            },
+            |version| self.get_or_err(version),
+        )
+    }
+
+    /// Insert a program set, replacing any existing set with the same version.
+    pub fn insert(&mut self, set: ProgramSet) {
+        self.sets.insert(set.version.clone(), set);
+    }
+
+    /// Get the program set of a version.
+    #[must_use]
+    pub fn get(&self, version: &str) -> Option<&ProgramSet> {
+        self.sets.get(version)
+    }
+
+    /// Get the versions in the registry.
+    pub fn versions(&self) -> impl Iterator<Item = &str> {
+        self.sets.keys().map(String::as_str)
+    }
+
+    /// Find the program set whose `program` verifying key matches `vkey`, encoded as in
+    /// [`HashableKey::bytes32`].
+    #[must_use]
+    pub fn find_by_vkey(&self, program: ProgramId, vkey: &str) -> Option<&ProgramSet> {
+        self.sets.values().find(|set| {
+            set.vkey(program)
+                .is_some_and(|set_vkey| set_vkey.bytes32().eq_ignore_ascii_case(vkey))
+        })
+    }
+}
@@ -1,7 +1,14 @@
 //! Prover for SP1 ICS07 Tendermint programs.
 
-use crate::programs::{
-    MembershipProgram, SP1Program, UpdateClientAndMembershipProgram, UpdateClientProgram,
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    cache::ProofCache,
+    preflight::preflight,
+    programs::{
+        registry::ProgramSet, MembershipProgram, ProgramId, SP1Program,
+        UpdateClientAndMembershipProgram, UpdateClientProgram,
+    },
 };
 use ibc_client_tendermint::types::Header;
 use ibc_core_commitment_types::merkle::MerkleProof;
@@ -20,6 +27,8 @@ pub struct SP1ICS07TendermintProver<T: SP1Program> {
     pub pkey: SP1ProvingKey,
     /// The verifying key.
     pub vkey: SP1VerifyingKey,
+    /// The cache consulted before proving.
+    cache: Option<ProofCache>,
     _phantom: std::marker::PhantomData<T>,
 }
 
@@ -41,9 +50,17 @@ impl<T: SP1Program> SP1ICS07TendermintProver<T> {
             prover_client,
             pkey,
             vkey,
+            cache: None,
             _phantom: std::marker::PhantomData,
         }
     }
+
+    /// Consult `cache` before proving, and store the new proofs in it.
+    #[must_use]
+    pub fn with_cache(mut self, cache: ProofCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
 }
 
 impl SP1ICS07TendermintProver<UpdateClientProgram> {
@@ -59,36 +76,17 @@ impl SP1ICS07TendermintProver<UpdateClientProgram> {
         proposed_header: &Header,
         contract_env: &Env,
     ) -> SP1ProofWithPublicValues {
-        // Encode the inputs into our program.
-        // NOTE: We are using SolConsensusState because I'm failing to serialize the
-        // ConsensusState struct properly. It always seems modified when deserialized.
-        let encoded_1 = bincode::serialize(&trusted_consensus_state).unwrap();
-        // NOTE: The Header struct is not deserializable by bincode, so we use CBOR instead.
-        let encoded_2 = serde_cbor::to_vec(proposed_header).unwrap();
-        let encoded_3 = bincode::serialize(contract_env).unwrap();
-        // TODO: find an encoding that works for all the structs above.
-
-        // Write the encoded light blocks to stdin.
-        let mut stdin = SP1Stdin::new();
-        stdin.write_vec(encoded_1);
-        stdin.write_vec(encoded_2);
-        stdin.write_vec(encoded_3);
-
-        // Generate the proof. Depending on SP1_PROVER env variable, this may be a mock, local or network proof.
-        let proof = self
-            .prover_client
-            .prove(&self.pkey, stdin)
-            .plonk()
-            .run()
-            .expect("proving failed");
-
-        // Verify proof.
-        self.prover_client
-            .verify(&proof, &self.vkey)
-            .expect("Verification failed");
-
-        // Return the proof.
-        proof
+        let stdin = update_client_stdin(trusted_consensus_state, proposed_header, contract_env);
+        prove_and_verify(
+            &self.prover_client,
+            UpdateClientProgram::ID,
+            &self.pkey,
+            &self.vkey,
+            stdin,
+            self.cache.as_ref(),
+            true,
+        )
+        .expect("proving failed")
     }
 }
 
@@ -103,34 +101,17 @@ impl SP1ICS07TendermintProver<MembershipProgram> {
         commitment_root: &[u8],
         kv_proofs: Vec<(String, MerkleProof, Vec<u8>)>,
     ) -> SP1ProofWithPublicValues {
-        assert!(!kv_proofs.is_empty(), "No key-value pairs to prove");
-        let len = u8::try_from(kv_proofs.len()).expect("too many key-value pairs");
-
-        let mut stdin = SP1Stdin::new();
-        stdin.write_slice(commitment_root);
-        stdin.write_vec(vec![len]);
-        for (path, proof, value) in kv_proofs {
-            stdin.write_slice(path.as_bytes());
-            stdin.write_vec(proof.encode_vec());
-            stdin.write_vec(value);
-        }
-
-        // Generate the proof. Depending on SP1_PROVER env variable, this may be a mock, local or
-        // network proof.
-        let proof = self
-            .prover_client
-            .prove(&self.pkey, stdin)
-            .plonk()
-            .run()
-            .expect("proving failed");
-
-        // Verify proof.
-        self.prover_client
-            .verify(&proof, &self.vkey)
-            .expect("Verification failed");
-
-        // Return the proof.
-        proof
+        let stdin = membership_stdin(commitment_root, kv_proofs);
+        prove_and_verify(
+            &self.prover_client,
+            MembershipProgram::ID,
+            &self.pkey,
+            &self.vkey,
+            stdin,
+            self.cache.as_ref(),
+            true,
+        )
+        .expect("proving failed")
     }
 }
 
@@ -150,43 +131,330 @@ impl SP1ICS07TendermintProver<UpdateClientAndMembershipProgram> {
         contract_env: &Env,
         kv_proofs: Vec<(String, MerkleProof, Vec<u8>)>,
     ) -> SP1ProofWithPublicValues {
-        assert!(!kv_proofs.is_empty(), "No key-value pairs to prove");
-        let len = u8::try_from(kv_proofs.len()).expect("too many key-value pairs");
-        // Encode the inputs into our program.
-        // NOTE: We are using SolConsensusState because I'm failing to serialize the
-        // ConsensusState struct properly. It always seems modified when deserialized.
-        let encoded_1 = bincode::serialize(&trusted_consensus_state).unwrap();
-        // NOTE: The Header struct is not deserializable by bincode, so we use CBOR instead.
-        let encoded_2 = serde_cbor::to_vec(proposed_header).unwrap();
-        let encoded_3 = bincode::serialize(contract_env).unwrap();
-        // TODO: find an encoding that works for all the structs above.
-
-        // Write the encoded light blocks to stdin.
-        let mut stdin = SP1Stdin::new();
-        stdin.write_vec(encoded_1);
-        stdin.write_vec(encoded_2);
-        stdin.write_vec(encoded_3);
-        stdin.write_vec(vec![len]);
-        for (path, proof, value) in kv_proofs {
-            stdin.write_slice(path.as_bytes());
-            stdin.write_vec(proof.encode_vec());
-            stdin.write_vec(value);
+        let stdin = uc_and_membership_stdin(
+            trusted_consensus_state,
+            proposed_header,
+            contract_env,
+            kv_proofs,
+        );
+        prove_and_verify(
+            &self.prover_client,
+            UpdateClientAndMembershipProgram::ID,
+            &self.pkey,
+            &self.vkey,
+            stdin,
+            self.cache.as_ref(),
+            true,
+        )
+        .expect("proving failed")
+    }
+}
+
+/// A prover that shares a single [`ProverClient`] between all the registered [`SP1Program`]s
+/// and dispatches proofs by [`ProgramId`].
+#[allow(clippy::module_name_repetitions)]
+pub struct SP1ICS07TendermintMultiProver {
+    /// [`sp1_sdk::ProverClient`] for generating proofs.
+    pub prover_client: ProverClient,
+    /// The proving and verifying keys of the registered programs.
+    keys: HashMap<ProgramId, (SP1ProvingKey, SP1VerifyingKey)>,
+    /// The registered programs whose ELF is the embedded one, which the preflight runs natively.
+    embedded: HashSet<ProgramId>,
+    /// The cache consulted before proving.
+    cache: Option<ProofCache>,
+}
+
+impl Default for SP1ICS07TendermintMultiProver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SP1ICS07TendermintMultiProver {
+    /// Create a new prover with all the SP1 ICS07 Tendermint programs registered.
+    #[must_use]
+    pub fn new() -> Self {
+        let mut prover = Self::empty();
+        prover.register::<UpdateClientProgram>();
+        prover.register::<MembershipProgram>();
+        prover.register::<UpdateClientAndMembershipProgram>();
+        prover
+    }
+
+    /// Create a new prover without any registered programs.
+    #[must_use]
+    pub fn empty() -> Self {
+        log::info!("Initializing SP1 ProverClient...");
+        let prover_client = ProverClient::new();
+        log::info!("SP1 ProverClient initialized");
+        Self {
+            prover_client,
+            keys: HashMap::new(),
+            embedded: HashSet::new(),
+            cache: None,
+        }
+    }
+
+    /// Consult `cache` before proving, and store the new proofs in it.
+    #[must_use]
+    pub fn with_cache(mut self, cache: ProofCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Create a new prover with all the programs of a [`ProgramSet`] registered.
+    #[must_use]
+    pub fn from_program_set(program_set: &ProgramSet) -> Self {
+        let mut prover = Self::empty();
+        for program in ProgramId::ALL {
+            if let Some(elf) = program_set.elf(program) {
+                prover.register_elf(program, elf);
+            }
+        }
+        prover
+    }
+
+    /// Register the program `T`, setting up its proving and verifying keys.
+    /// Registering an already registered program is a no-op.
+    pub fn register<T: SP1Program>(&mut self) {
+        if !self.keys.contains_key(&T::ID) {
+            self.register_elf(T::ID, T::ELF);
         }
+    }
 
-        // Generate the proof. Depending on SP1_PROVER env variable, this may be a mock, local or network proof.
-        let proof = self
-            .prover_client
-            .prove(&self.pkey, stdin)
-            .plonk()
-            .run()
-            .expect("proving failed");
+    /// Register `elf` as `program`, setting up its proving and verifying keys.
+    /// Replaces the keys of an already registered program.
+    pub fn register_elf(&mut self, program: ProgramId, elf: &[u8]) {
+        log::info!("Setting up the keys for the {program} program...");
+        let keys = self.prover_client.setup(elf);
+        self.keys.insert(program, keys);
+        if elf == program.elf() {
+            self.embedded.insert(program);
+        } else {
+            self.embedded.remove(&program);
+        }
+    }
 
-        // Verify proof.
-        self.prover_client
-            .verify(&proof, &self.vkey)
-            .expect("Verification failed");
+    /// Get the verifying key of a registered program.
+    #[must_use]
+    pub fn vkey(&self, program: ProgramId) -> Option<&SP1VerifyingKey> {
+        self.keys.get(&program).map(|(_, vkey)| vkey)
+    }
 
-        // Return the proof.
-        proof
+    /// Verify a proof of a registered program, such as one generated by a remote worker.
+    ///
+    /// # Errors
+    /// Returns an error if the program is not registered or the proof is invalid.
+    pub fn verify(
+        &self,
+        program: ProgramId,
+        proof: &SP1ProofWithPublicValues,
+    ) -> anyhow::Result<()> {
+        let vkey = self
+            .vkey(program)
+            .ok_or_else(|| anyhow::anyhow!("program {program} is not registered"))?;
+        self.prover_client.verify(proof, vkey)?;
+        Ok(())
+    }
+
+    /// Generate and verify a proof for `program` with the given `stdin`.
+    ///
+    /// # Errors
+    /// Returns an error if the program is not registered, the proof cannot be generated or the
+    /// proof is invalid.
+    pub fn prove(
+        &self,
+        program: ProgramId,
+        stdin: SP1Stdin,
+    ) -> anyhow::Result<SP1ProofWithPublicValues> {
+        let (pkey, vkey) = self
+            .keys
+            .get(&program)
+            .ok_or_else(|| anyhow::anyhow!("program {program} is not registered"))?;
+        prove_and_verify(
+            &self.prover_client,
+            program,
+            pkey,
+            vkey,
+            stdin,
+            self.cache.as_ref(),
+            self.embedded.contains(&program),
+        )
+    }
+
+    /// Generate a proof with the [`UpdateClientProgram`].
+    /// See [`SP1ICS07TendermintProver::<UpdateClientProgram>::generate_proof`].
+    ///
+    /// # Errors
+    /// Returns an error if the program is not registered, the proof cannot be generated or the
+    /// proof is invalid.
+    pub fn prove_update_client(
+        &self,
+        trusted_consensus_state: &SolConsensusState,
+        proposed_header: &Header,
+        contract_env: &Env,
+    ) -> anyhow::Result<SP1ProofWithPublicValues> {
+        let stdin = update_client_stdin(trusted_consensus_state, proposed_header, contract_env);
+        self.prove(ProgramId::UpdateClient, stdin)
+    }
+
+    /// Generate a proof with the [`MembershipProgram`].
+    /// See [`SP1ICS07TendermintProver::<MembershipProgram>::generate_proof`].
+    ///
+    /// # Errors
+    /// Returns an error if the program is not registered, the proof cannot be generated or the
+    /// proof is invalid.
+    pub fn prove_membership(
+        &self,
+        commitment_root: &[u8],
+        kv_proofs: Vec<(String, MerkleProof, Vec<u8>)>,
+    ) -> anyhow::Result<SP1ProofWithPublicValues> {
+        let stdin = membership_stdin(commitment_root, kv_proofs);
+        self.prove(ProgramId::Membership, stdin)
+    }
+
+    /// Generate a proof with the [`UpdateClientAndMembershipProgram`].
+    /// See [`SP1ICS07TendermintProver::<UpdateClientAndMembershipProgram>::generate_proof`].
+    ///
+    /// # Errors
+    /// Returns an error if the program is not registered, the proof cannot be generated or the
+    /// proof is invalid.
+    pub fn prove_update_client_and_membership(
+        &self,
+        trusted_consensus_state: &SolConsensusState,
+        proposed_header: &Header,
+        contract_env: &Env,
+        kv_proofs: Vec<(String, MerkleProof, Vec<u8>)>,
+    ) -> anyhow::Result<SP1ProofWithPublicValues> {
+        let stdin = uc_and_membership_stdin(
+            trusted_consensus_state,
+            proposed_header,
+            contract_env,
+            kv_proofs,
+        );
+        self.prove(ProgramId::UpdateClientAndMembership, stdin)
+    }
+}
+
+/// Encode the inputs of the [`UpdateClientProgram`].
+///
+/// # Panics
+/// Panics if the inputs cannot be encoded.
+#[must_use]
+pub fn update_client_stdin(
+    trusted_consensus_state: &SolConsensusState,
+    proposed_header: &Header,
+    contract_env: &Env,
+) -> SP1Stdin {
+    // Encode the inputs into our program.
+    // NOTE: We are using SolConsensusState because I'm failing to serialize the
+    // ConsensusState struct properly. It always seems modified when deserialized.
+    let encoded_1 = bincode::serialize(&trusted_consensus_state).unwrap();
+    // NOTE: The Header struct is not deserializable by bincode, so we use CBOR instead.
+    let encoded_2 = serde_cbor::to_vec(proposed_header).unwrap();
+    let encoded_3 = bincode::serialize(contract_env).unwrap();
+    // TODO: find an encoding that works for all the structs above.
+
+    // Write the encoded light blocks to stdin.
+    let mut stdin = SP1Stdin::new();
+    stdin.write_vec(encoded_1);
+    stdin.write_vec(encoded_2);
+    stdin.write_vec(encoded_3);
+    stdin
+}
+
+/// Encode the inputs of the [`MembershipProgram`].
+///
+/// # Panics
+/// Panics if `kv_proofs` is empty or has more than 255 entries.
+#[must_use]
+pub fn membership_stdin(
+    commitment_root: &[u8],
+    kv_proofs: Vec<(String, MerkleProof, Vec<u8>)>,
+) -> SP1Stdin {
+    assert!(!kv_proofs.is_empty(), "No key-value pairs to prove");
+    let len = u8::try_from(kv_proofs.len()).expect("too many key-value pairs");
+
+    let mut stdin = SP1Stdin::new();
+    stdin.write_slice(commitment_root);
+    stdin.write_vec(vec![len]);
+    write_kv_proofs(&mut stdin, kv_proofs);
+    stdin
+}
+
+/// Encode the inputs of the [`UpdateClientAndMembershipProgram`].
+///
+/// # Panics
+/// Panics if the inputs cannot be encoded, or if `kv_proofs` is empty or has more than 255
+/// entries.
+#[must_use]
+pub fn uc_and_membership_stdin(
+    trusted_consensus_state: &SolConsensusState,
+    proposed_header: &Header,
+    contract_env: &Env,
+    kv_proofs: Vec<(String, MerkleProof, Vec<u8>)>,
+) -> SP1Stdin {
+    assert!(!kv_proofs.is_empty(), "No key-value pairs to prove");
+    let len = u8::try_from(kv_proofs.len()).expect("too many key-value pairs");
+
+    let mut stdin = update_client_stdin(trusted_consensus_state, proposed_header, contract_env);
+    stdin.write_vec(vec![len]);
+    write_kv_proofs(&mut stdin, kv_proofs);
+    stdin
+}
+
+/// Write the key-value proofs to `stdin` in the order the programs read them.
+fn write_kv_proofs(stdin: &mut SP1Stdin, kv_proofs: Vec<(String, MerkleProof, Vec<u8>)>) {
+    for (path, proof, value) in kv_proofs {
+        stdin.write_slice(path.as_bytes());
+        stdin.write_vec(proof.encode_vec());
+        stdin.write_vec(value);
+    }
+}
+
+/// Generate a proof and verify it before returning.
+/// Depending on `SP1_PROVER` env variable, this may be a mock, local or network proof.
+///
+/// A fresh proof of the same inputs in `cache` is returned instead of proving, if any. Otherwise
+/// the program is first run natively on the same inputs if it is `embedded`, see [`preflight`],
+/// and the new proof is stored in `cache`. The preflight runs the embedded code, so it is skipped
+/// for the other versions of the program.
+fn prove_and_verify(
+    prover_client: &ProverClient,
+    program: ProgramId,
+    pkey: &SP1ProvingKey,
+    vkey: &SP1VerifyingKey,
+    stdin: SP1Stdin,
+    cache: Option<&ProofCache>,
+    embedded: bool,
+) -> anyhow::Result<SP1ProofWithPublicValues> {
+    if let Some(proof) = cache.and_then(|cache| cache.get(program, vkey, &stdin.buffer)) {
+        match prover_client.verify(&proof, vkey) {
+            Ok(()) => {
+                log::info!("Using a cached proof for the {program} program");
+                return Ok(proof);
+            }
+            Err(e) => log::warn!("Ignoring an invalid cached {program} proof: {e}"),
+        }
+    }
+
+    if embedded {
+        preflight(program, &stdin.buffer)?;
+        log::debug!("Preflight of the {program} program succeeded");
+    } else {
+        log::info!(
+            "Skipping the preflight of the {program} program, which is not the embedded one"
+        );
+    }
+
+    let buffer = cache.map(|_| stdin.buffer.clone());
+    let proof = prover_client.prove(pkey, stdin).plonk().run()?;
+    prover_client.verify(&proof, vkey)?;
+
+    if let (Some(cache), Some(buffer)) = (cache, buffer) {
+        if let Err(e) = cache.insert(program, vkey, &buffer, &proof) {
+            log::warn!("Failed to cache the {program} proof: {e}");
+        }
     }
+    Ok(proof)
 }
@@ -0,0 +1,169 @@
+//! The HTTP client of the coordinator, used by workers and by the operator to prove remotely.
+
+use std::time::Duration;
+
+use reqwest::{
+    header::{HeaderMap, HeaderValue, AUTHORIZATION},
+    Url,
+};
+use sp1_sdk::SP1ProofWithPublicValues;
+
+use super::{
+    AssignedJob, JobOutcome, JobReport, JobStatus, RegisterWorkerRequest, RegisterWorkerResponse,
+    SubmitJobResponse,
+};
+use crate::{job::ProofJob, prover::SP1ICS07TendermintMultiProver};
+
+/// A client of the coordinator.
+#[derive(Clone, Debug)]
+pub struct CoordinatorClient {
+    http: reqwest::Client,
+    url: Url,
+}
+
+impl CoordinatorClient {
+    /// Create a new client for the coordinator at `url`, authenticating with `token` if given.
+    ///
+    /// # Errors
+    /// Returns an error if the URL cannot be parsed, or if the token is not a valid header value.
+    pub fn new(url: &str, token: Option<&str>) -> anyhow::Result<Self> {
+        let mut headers = HeaderMap::new();
+        if let Some(token) = token {
+            let mut value = HeaderValue::from_str(&format!("Bearer {token}"))?;
+            value.set_sensitive(true);
+            headers.insert(AUTHORIZATION, value);
+        }
+        Ok(Self {
+            http: reqwest::Client::builder()
+                .default_headers(headers)
+                .build()?,
+            url: Url::parse(url)?,
+        })
+    }
+
+    /// Register a worker with the coordinator.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails.
+    pub async fn register_worker(&self, name: &str) -> anyhow::Result<u64> {
+        let res: RegisterWorkerResponse = self
+            .http
+            .post(self.url.join("workers")?)
+            .json(&RegisterWorkerRequest {
+                name: name.to_string(),
+            })
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(res.worker_id)
+    }
+
+    /// Get the next job assigned to the worker, if any.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails.
+    pub async fn next_job(&self, worker_id: u64) -> anyhow::Result<Option<AssignedJob>> {
+        Ok(self
+            .http
+            .post(self.url.join(&format!("workers/{worker_id}/jobs"))?)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    /// Report the outcome of an assigned job.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails.
+    pub async fn report_job(
+        &self,
+        worker_id: u64,
+        job_id: u64,
+        outcome: JobOutcome,
+    ) -> anyhow::Result<()> {
+        self.http
+            .post(self.url.join(&format!("jobs/{job_id}/result"))?)
+            .json(&JobReport { worker_id, outcome })
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// Submit a job to the coordinator.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails.
+    pub async fn submit_job(&self, job: &ProofJob) -> anyhow::Result<u64> {
+        let res: SubmitJobResponse = self
+            .http
+            .post(self.url.join("jobs")?)
+            .json(job)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(res.job_id)
+    }
+
+    /// Get the status of a job.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails.
+    pub async fn job_status(&self, job_id: u64) -> anyhow::Result<JobStatus> {
+        Ok(self
+            .http
+            .get(self.url.join(&format!("jobs/{job_id}"))?)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+
+    /// Submit a job and wait for a worker to prove it, polling every `poll_interval`. The proof is
+    /// checked with the job's program registered in `verifier` before it is returned.
+    ///
+    /// # Errors
+    /// Returns an error if a request fails, if the job fails, if the job is proven with another
+    /// verifying key, or if the proof is invalid.
+    pub async fn prove(
+        &self,
+        job: &ProofJob,
+        verifier: &SP1ICS07TendermintMultiProver,
+        poll_interval: Duration,
+    ) -> anyhow::Result<SP1ProofWithPublicValues> {
+        let job_id = self.submit_job(job).await?;
+        log::info!("Submitted {} job {job_id} to the coordinator", job.program);
+
+        loop {
+            match self.job_status(job_id).await? {
+                JobStatus::Pending | JobStatus::Assigned { .. } => {
+                    tokio::time::sleep(poll_interval).await;
+                }
+                JobStatus::Done { result } => {
+                    anyhow::ensure!(
+                        result.job.vkey.eq_ignore_ascii_case(&job.vkey),
+                        "job {job_id} was proven with vkey {} instead of {}",
+                        result.job.vkey,
+                        job.vkey
+                    );
+                    // Workers are not trusted with the validity of the proof.
+                    let proof = result.sp1_proof()?;
+                    verifier.verify(job.program, &proof).map_err(|e| {
+                        anyhow::anyhow!("job {job_id} returned an invalid proof: {e}")
+                    })?;
+                    return Ok(proof);
+                }
+                JobStatus::Failed { error } => {
+                    anyhow::bail!("job {job_id} failed: {error}");
+                }
+            }
+        }
+    }
+}
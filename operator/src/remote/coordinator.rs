@@ -0,0 +1,293 @@
+//! The coordinator that queues proof jobs and assigns them to workers.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use axum::{
+    extract::{Path, Request, State},
+    http::{header::AUTHORIZATION, StatusCode},
+    middleware::{self, Next},
+    response::Response,
+    routing::{get, post},
+    Json, Router,
+};
+
+use super::{
+    AssignedJob, JobOutcome, JobReport, JobStatus, RegisterWorkerRequest, RegisterWorkerResponse,
+    SubmitJobResponse,
+};
+use crate::job::{ProofJob, ProofJobResult};
+
+/// The coordinator of the remote workers.
+pub struct Coordinator {
+    state: Mutex<CoordinatorState>,
+    job_timeout: Duration,
+    result_ttl: Duration,
+    token: Option<String>,
+}
+
+#[derive(Default)]
+struct CoordinatorState {
+    next_worker_id: u64,
+    next_job_id: u64,
+    workers: HashMap<u64, String>,
+    queue: VecDeque<u64>,
+    jobs: HashMap<u64, JobEntry>,
+}
+
+struct JobEntry {
+    job: ProofJob,
+    status: JobStatus,
+    assigned_at: Option<Instant>,
+    finished_at: Option<Instant>,
+}
+
+impl Coordinator {
+    /// Create a new coordinator. Jobs assigned to a worker for longer than `job_timeout` are
+    /// assigned to another worker. Finished jobs are dropped `result_ttl` after they finish.
+    #[must_use]
+    pub fn new(job_timeout: Duration, result_ttl: Duration) -> Self {
+        Self {
+            state: Mutex::new(CoordinatorState::default()),
+            job_timeout,
+            result_ttl,
+            token: None,
+        }
+    }
+
+    /// Require every request to carry the header `Authorization: Bearer <token>`.
+    #[must_use]
+    pub fn with_token(mut self, token: String) -> Self {
+        self.token = Some(token);
+        self
+    }
+
+    /// Serve the coordinator on `addr`.
+    ///
+    /// # Errors
+    /// Returns an error if the address cannot be bound or the server fails.
+    pub async fn serve(self, addr: SocketAddr) -> anyhow::Result<()> {
+        let app = Router::new()
+            .route("/workers", post(register_worker))
+            .route("/workers/:worker_id/jobs", post(next_job))
+            .route("/jobs", post(submit_job))
+            .route("/jobs/:job_id", get(job_status))
+            .route("/jobs/:job_id/result", post(report_job));
+        let coordinator = Arc::new(self);
+        let app = app
+            .route_layer(middleware::from_fn_with_state(
+                Arc::clone(&coordinator),
+                authorize,
+            ))
+            .with_state(coordinator);
+
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        log::info!("Coordinator listening on {addr}");
+        axum::serve(listener, app).await?;
+        Ok(())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, CoordinatorState> {
+        let mut state = self.state.lock().expect("coordinator state poisoned");
+        state.evict_finished(self.result_ttl);
+        state
+    }
+}
+
+impl CoordinatorState {
+    /// Drops the jobs that finished more than `result_ttl` ago.
+    fn evict_finished(&mut self, result_ttl: Duration) {
+        self.jobs.retain(|job_id, entry| {
+            let expired = entry
+                .finished_at
+                .is_some_and(|at| at.elapsed() > result_ttl);
+            if expired {
+                log::debug!("Dropping the result of job {job_id}");
+            }
+            !expired
+        });
+    }
+
+    fn register_worker(&mut self, name: String) -> u64 {
+        let worker_id = self.next_worker_id;
+        self.next_worker_id += 1;
+        log::info!("Registered worker {worker_id} ({name})");
+        self.workers.insert(worker_id, name);
+        worker_id
+    }
+
+    fn next_job(
+        &mut self,
+        worker_id: u64,
+        job_timeout: Duration,
+    ) -> Result<Option<AssignedJob>, StatusCode> {
+        if !self.workers.contains_key(&worker_id) {
+            return Err(StatusCode::NOT_FOUND);
+        }
+
+        // Requeue the jobs whose worker did not report in time.
+        let mut expired: Vec<u64> = self
+            .jobs
+            .iter()
+            .filter(|(_, entry)| {
+                matches!(entry.status, JobStatus::Assigned { .. })
+                    && entry
+                        .assigned_at
+                        .is_some_and(|at| at.elapsed() > job_timeout)
+            })
+            .map(|(job_id, _)| *job_id)
+            .collect();
+        expired.sort_unstable();
+        for job_id in expired {
+            log::warn!("Job {job_id} timed out, requeueing");
+            if let Some(entry) = self.jobs.get_mut(&job_id) {
+                entry.status = JobStatus::Pending;
+                entry.assigned_at = None;
+            }
+            self.queue.push_back(job_id);
+        }
+
+        let Some(job_id) = self.queue.pop_front() else {
+            return Ok(None);
+        };
+        let entry = self.jobs.get_mut(&job_id).expect("queued jobs are tracked");
+        entry.status = JobStatus::Assigned { worker_id };
+        entry.assigned_at = Some(Instant::now());
+        log::info!(
+            "Assigned {} job {job_id} to worker {worker_id}",
+            entry.job.program
+        );
+
+        Ok(Some(AssignedJob {
+            job_id,
+            job: entry.job.clone(),
+        }))
+    }
+
+    fn submit_job(&mut self, job: ProofJob) -> u64 {
+        let job_id = self.next_job_id;
+        self.next_job_id += 1;
+        log::info!("Received {} job {job_id}", job.program);
+        self.jobs.insert(
+            job_id,
+            JobEntry {
+                job,
+                status: JobStatus::Pending,
+                assigned_at: None,
+                finished_at: None,
+            },
+        );
+        self.queue.push_back(job_id);
+        job_id
+    }
+
+    /// Gets the status of a job. Finished jobs are kept until they are evicted, so that a
+    /// submitter whose fetch failed can fetch the result again.
+    fn job_status(&self, job_id: u64) -> Option<JobStatus> {
+        self.jobs.get(&job_id).map(|entry| entry.status.clone())
+    }
+
+    fn report_job(&mut self, job_id: u64, report: JobReport) -> Result<(), StatusCode> {
+        let entry = self.jobs.get_mut(&job_id).ok_or(StatusCode::NOT_FOUND)?;
+        match entry.status {
+            JobStatus::Assigned { worker_id } if worker_id == report.worker_id => {}
+            // The job was requeued or reported by another worker in the meantime.
+            _ => return Err(StatusCode::CONFLICT),
+        }
+
+        entry.assigned_at = None;
+        entry.finished_at = Some(Instant::now());
+        entry.status = match report.outcome {
+            JobOutcome::Proven { vkey, proof } => {
+                log::info!("Worker {} proved job {job_id}", report.worker_id);
+                let mut job = entry.job.clone();
+                job.vkey = vkey;
+                JobStatus::Done {
+                    result: ProofJobResult { job, proof },
+                }
+            }
+            JobOutcome::Failed { error } => {
+                log::error!("Worker {} failed job {job_id}: {error}", report.worker_id);
+                JobStatus::Failed { error }
+            }
+        };
+        Ok(())
+    }
+}
+
+/// Rejects the requests without the token of the coordinator, if it has one.
+async fn authorize(
+    State(coordinator): State<Arc<Coordinator>>,
+    req: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    if let Some(token) = &coordinator.token {
+        let authorized = req
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .is_some_and(|given| constant_time_eq(given.as_bytes(), token.as_bytes()));
+        if !authorized {
+            return Err(StatusCode::UNAUTHORIZED);
+        }
+    }
+    Ok(next.run(req).await)
+}
+
+/// Compares two byte strings in a time independent of where they differ.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[allow(clippy::unused_async)]
+async fn register_worker(
+    State(coordinator): State<Arc<Coordinator>>,
+    Json(req): Json<RegisterWorkerRequest>,
+) -> Json<RegisterWorkerResponse> {
+    let worker_id = coordinator.lock().register_worker(req.name);
+    Json(RegisterWorkerResponse { worker_id })
+}
+
+#[allow(clippy::unused_async)]
+async fn next_job(
+    State(coordinator): State<Arc<Coordinator>>,
+    Path(worker_id): Path<u64>,
+) -> Result<Json<Option<AssignedJob>>, StatusCode> {
+    let job = coordinator
+        .lock()
+        .next_job(worker_id, coordinator.job_timeout)?;
+    Ok(Json(job))
+}
+
+#[allow(clippy::unused_async)]
+async fn submit_job(
+    State(coordinator): State<Arc<Coordinator>>,
+    Json(job): Json<ProofJob>,
+) -> Json<SubmitJobResponse> {
+    let job_id = coordinator.lock().submit_job(job);
+    Json(SubmitJobResponse { job_id })
+}
+
+#[allow(clippy::unused_async)]
+async fn job_status(
+    State(coordinator): State<Arc<Coordinator>>,
+    Path(job_id): Path<u64>,
+) -> Result<Json<JobStatus>, StatusCode> {
+    let status = coordinator.lock().job_status(job_id);
+    status.map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+#[allow(clippy::unused_async)]
+async fn report_job(
+    State(coordinator): State<Arc<Coordinator>>,
+    Path(job_id): Path<u64>,
+    Json(report): Json<JobReport>,
+) -> Result<StatusCode, StatusCode> {
+    coordinator.lock().report_job(job_id, report)?;
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,107 @@
+//! Remote proving: a coordinator distributes [`ProofJob`]s to worker processes over HTTP.
+//!
+//! The protocol is:
+//! - `POST /workers` registers a worker and returns its identifier.
+//! - `POST /workers/:worker_id/jobs` assigns the next pending job to the worker, if any.
+//! - `POST /jobs` submits a job and returns its identifier.
+//! - `GET /jobs/:job_id` returns the status of a job, including its result once proven. A
+//!   finished job is dropped once its status is returned.
+//! - `POST /jobs/:job_id/result` reports the outcome of an assigned job.
+//!
+//! If the coordinator has a token, every request must carry it in an
+//! `Authorization: Bearer <token>` header.
+
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+
+use crate::job::{ProofJob, ProofJobResult};
+
+pub mod client;
+pub mod coordinator;
+
+/// The request to register a worker.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterWorkerRequest {
+    /// A human readable name of the worker.
+    pub name: String,
+}
+
+/// The response to [`RegisterWorkerRequest`].
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterWorkerResponse {
+    /// The identifier assigned to the worker.
+    pub worker_id: u64,
+}
+
+/// The response to a job submission.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmitJobResponse {
+    /// The identifier assigned to the job.
+    pub job_id: u64,
+}
+
+/// A job assigned to a worker.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssignedJob {
+    /// The identifier of the job.
+    pub job_id: u64,
+    /// The job to prove.
+    pub job: ProofJob,
+}
+
+/// The report of a worker on an assigned job.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobReport {
+    /// The identifier of the reporting worker.
+    pub worker_id: u64,
+    /// The outcome of the job.
+    pub outcome: JobOutcome,
+}
+
+/// The outcome of proving a job.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum JobOutcome {
+    /// The job was proven.
+    Proven {
+        /// The verifying key the job was proven with.
+        vkey: String,
+        /// The bincode encoded [`sp1_sdk::SP1ProofWithPublicValues`].
+        #[serde_as(as = "serde_with::hex::Hex")]
+        proof: Vec<u8>,
+    },
+    /// The job could not be proven.
+    Failed {
+        /// The error message.
+        error: String,
+    },
+}
+
+/// The status of a job.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum JobStatus {
+    /// The job is waiting for a worker.
+    Pending,
+    /// The job is being proven by a worker.
+    Assigned {
+        /// The identifier of the worker.
+        worker_id: u64,
+    },
+    /// The job was proven.
+    Done {
+        /// The result of the job.
+        result: ProofJobResult,
+    },
+    /// The job could not be proven.
+    Failed {
+        /// The error message.
+        error: String,
+    },
+}
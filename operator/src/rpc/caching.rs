@@ -0,0 +1,189 @@
+//! A [`LightBlockProvider`] that caches the responses of another provider.
+
+use std::{collections::BTreeMap, sync::Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use tendermint::{block::signed_header::SignedHeader, node::Id as PeerId};
+use tendermint_light_client_verifier::{
+    options::Options,
+    types::{LightBlock, ValidatorSet},
+};
+use tendermint_rpc::endpoint::abci_query::AbciQuery;
+
+use super::{
+    CassetteProvider, FailoverProvider, HttpProvider, LightBlockProvider, WitnessedProvider,
+};
+
+/// The default number of heights kept in the cache.
+pub const DEFAULT_CACHE_CAPACITY: usize = 128;
+
+/// A [`LightBlockProvider`] that caches the signed headers and validator sets of another
+/// provider by height.
+///
+/// Consecutive light blocks share a validator set: the next validators of a block are the
+/// validators of the following block, so only the missing parts of a light block are fetched.
+/// ABCI queries are not cached.
+pub struct CachingProvider<P> {
+    inner: P,
+    capacity: usize,
+    cache: Mutex<Cache>,
+}
+
+#[derive(Default)]
+struct Cache {
+    peer_id: Option<PeerId>,
+    signed_headers: BTreeMap<u32, SignedHeader>,
+    validators: BTreeMap<u32, ValidatorSet>,
+}
+
+impl<P: LightBlockProvider> CachingProvider<P> {
+    /// Creates a new caching provider around `inner`.
+    #[must_use]
+    pub fn new(inner: P) -> Self {
+        Self::with_capacity(inner, DEFAULT_CACHE_CAPACITY)
+    }
+
+    /// Creates a new caching provider around `inner`, keeping at most `capacity` heights of
+    /// headers and validator sets. The lowest heights are evicted first.
+    #[must_use]
+    pub fn with_capacity(inner: P, capacity: usize) -> Self {
+        Self {
+            inner,
+            capacity,
+            cache: Mutex::new(Cache::default()),
+        }
+    }
+
+    /// Gets the wrapped provider.
+    #[must_use]
+    pub const fn inner(&self) -> &P {
+        &self.inner
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Cache> {
+        self.cache.lock().expect("light block cache poisoned")
+    }
+
+    fn insert_signed_header(&self, height: u32, signed_header: SignedHeader) {
+        let mut cache = self.lock();
+        cache.signed_headers.insert(height, signed_header);
+        while cache.signed_headers.len() > self.capacity {
+            cache.signed_headers.pop_first();
+        }
+    }
+
+    fn insert_validators(&self, height: u32, validators: ValidatorSet) {
+        let mut cache = self.lock();
+        cache.validators.insert(height, validators);
+        while cache.validators.len() > self.capacity {
+            cache.validators.pop_first();
+        }
+    }
+}
+
+impl CachingProvider<WitnessedProvider<CassetteProvider<FailoverProvider<HttpProvider>>>> {
+    /// Creates a new caching provider for the endpoints, witnesses and cassette in the
+    /// environment variables. See [`FailoverProvider::from_env`],
+    /// [`WitnessedProvider::from_env`] and [`CassetteProvider::from_env`].
+    ///
+    /// The witnesses are not recorded in the cassette, so they are disabled when replaying it.
+    ///
+    /// # Panics
+    /// Panics if the `TENDERMINT_RPC_URL` environment variable is not set, if the URLs are
+    /// invalid, or if the cassette cannot be replayed.
+    #[must_use]
+    pub fn from_env() -> Self {
+        let cassette = CassetteProvider::from_env();
+        if cassette.is_replaying() {
+            log::info!("Replaying a cassette, the headers are not cross-checked with witnesses");
+            return Self::new(WitnessedProvider::new(cassette, vec![]));
+        }
+        Self::new(WitnessedProvider::from_env(cassette))
+    }
+}
+
+#[async_trait]
+impl<P: LightBlockProvider> LightBlockProvider for CachingProvider<P> {
+    fn trust(&self, trusted_light_block: &LightBlock, options: &Options) {
+        self.inner.trust(trusted_light_block, options);
+    }
+
+    fn pin(&self) {
+        self.inner.pin();
+    }
+
+    fn unpin(&self) {
+        self.inner.unpin();
+    }
+
+    async fn peer_id(&self) -> Result<PeerId> {
+        let cached = self.lock().peer_id;
+        if let Some(peer_id) = cached {
+            return Ok(peer_id);
+        }
+        let peer_id = self.inner.peer_id().await?;
+        self.lock().peer_id = Some(peer_id);
+        Ok(peer_id)
+    }
+
+    async fn latest_height(&self) -> Result<u32> {
+        self.inner.latest_height().await
+    }
+
+    async fn signed_header(&self, height: u32) -> Result<SignedHeader> {
+        let cached = self.lock().signed_headers.get(&height).cloned();
+        if let Some(signed_header) = cached {
+            return Ok(signed_header);
+        }
+        let signed_header = self.inner.signed_header(height).await?;
+        self.insert_signed_header(height, signed_header.clone());
+        Ok(signed_header)
+    }
+
+    async fn validators(&self, height: u32) -> Result<ValidatorSet> {
+        let cached = self.lock().validators.get(&height).cloned();
+        if let Some(validators) = cached {
+            return Ok(validators);
+        }
+        let validators = self.inner.validators(height).await?;
+        self.insert_validators(height, validators.clone());
+        Ok(validators)
+    }
+
+    async fn abci_query(
+        &self,
+        path: Option<String>,
+        data: Vec<u8>,
+        height: Option<u32>,
+        prove: bool,
+    ) -> Result<AbciQuery> {
+        self.inner.abci_query(path, data, height, prove).await
+    }
+
+    async fn light_block(&self, height: u32) -> Result<LightBlock> {
+        let is_cached = {
+            let cache = self.lock();
+            cache.signed_headers.contains_key(&height)
+                || cache.validators.contains_key(&height)
+                || cache.validators.contains_key(&(height + 1))
+        };
+
+        // Fetch the whole light block at once if none of it is cached.
+        if !is_cached {
+            let light_block = self.inner.light_block(height).await?;
+            self.lock().peer_id = Some(light_block.provider);
+            self.insert_signed_header(height, light_block.signed_header.clone());
+            self.insert_validators(height, light_block.validators.clone());
+            self.insert_validators(height + 1, light_block.next_validators.clone());
+            return Ok(light_block);
+        }
+
+        Ok(LightBlock::new(
+            self.signed_header(height).await?,
+            self.validators(height).await?,
+            self.validators(height + 1).await?,
+            self.peer_id().await?,
+        ))
+    }
+}
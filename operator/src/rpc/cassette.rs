@@ -0,0 +1,315 @@
+//! A [`LightBlockProvider`] that records the requests and responses of another provider to a
+//! cassette file, or replays them without network access.
+
+use std::{
+    collections::{HashMap, VecDeque},
+    env,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_with::serde_as;
+use tendermint::{block::signed_header::SignedHeader, node::Id as PeerId};
+use tendermint_light_client_verifier::{
+    options::Options,
+    types::{LightBlock, ValidatorSet},
+};
+use tendermint_rpc::endpoint::abci_query::AbciQuery;
+
+use super::{FailoverProvider, HttpProvider, LightBlockProvider};
+
+/// A request to a [`LightBlockProvider`].
+#[serde_as]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+enum Request {
+    PeerId,
+    LatestHeight,
+    SignedHeader {
+        height: u32,
+    },
+    Validators {
+        height: u32,
+    },
+    LightBlock {
+        height: u32,
+    },
+    AbciQuery {
+        path: Option<String>,
+        #[serde_as(as = "serde_with::hex::Hex")]
+        data: Vec<u8>,
+        height: Option<u32>,
+        prove: bool,
+    },
+}
+
+/// A recorded request and its response.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct Interaction {
+    request: Request,
+    response: serde_json::Value,
+}
+
+/// The contents of a cassette file, in the order of the requests.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+struct Cassette {
+    interactions: Vec<Interaction>,
+}
+
+enum Mode<P> {
+    /// Forward the requests without recording them.
+    Disabled(P),
+    /// Forward the requests and record the responses, writing the cassette at `path` after
+    /// every response so that the recording survives an interrupted process.
+    Recording {
+        inner: P,
+        path: PathBuf,
+        cassette: Mutex<Cassette>,
+    },
+    /// Serve the recorded responses, in the order they were recorded. The last response to a
+    /// request is served again once the others are used up.
+    Replaying(Mutex<HashMap<Request, VecDeque<serde_json::Value>>>),
+}
+
+/// A [`LightBlockProvider`] that records every request and response of another provider to a
+/// cassette file, or replays a cassette without network access.
+///
+/// Recorded runs can be replayed deterministically, for example to regenerate fixtures or to run
+/// the operator in integration tests.
+pub struct CassetteProvider<P> {
+    mode: Mode<P>,
+}
+
+impl<P: LightBlockProvider> CassetteProvider<P> {
+    /// Creates a new provider forwarding the requests to `inner` without recording them.
+    #[must_use]
+    pub const fn disabled(inner: P) -> Self {
+        Self {
+            mode: Mode::Disabled(inner),
+        }
+    }
+
+    /// Creates a new provider forwarding the requests to `inner` and recording the responses to
+    /// the cassette at `path`. The cassette is written after every response.
+    #[must_use]
+    pub fn record(inner: P, path: impl Into<PathBuf>) -> Self {
+        Self {
+            mode: Mode::Recording {
+                inner,
+                path: path.into(),
+                cassette: Mutex::new(Cassette::default()),
+            },
+        }
+    }
+
+    /// Creates a new provider replaying the cassette at `path`.
+    ///
+    /// # Errors
+    /// Returns an error if the cassette cannot be read or parsed.
+    pub fn replay(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read cassette {}", path.display()))?;
+        let cassette: Cassette = serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse cassette {}", path.display()))?;
+
+        let mut responses: HashMap<_, VecDeque<_>> = HashMap::new();
+        for interaction in cassette.interactions {
+            responses
+                .entry(interaction.request)
+                .or_default()
+                .push_back(interaction.response);
+        }
+        Ok(Self {
+            mode: Mode::Replaying(Mutex::new(responses)),
+        })
+    }
+
+    /// Performs `request`, recording or replaying it depending on the mode.
+    async fn call<T, F>(&self, request: Request, forward: F) -> Result<T>
+    where
+        T: Serialize + DeserializeOwned + Send,
+        F: for<'a> FnOnce(&'a P) -> BoxFuture<'a, Result<T>> + Send,
+    {
+        match &self.mode {
+            Mode::Disabled(inner) => forward(inner).await,
+            Mode::Recording {
+                inner,
+                path,
+                cassette,
+            } => {
+                let response = forward(inner).await?;
+                let interaction = Interaction {
+                    request,
+                    response: serde_json::to_value(&response)?,
+                };
+                // The lock is held while writing, so that concurrent writes stay in order.
+                let mut cassette = cassette.lock().expect("cassette poisoned");
+                cassette.interactions.push(interaction);
+                write_cassette(path, &cassette)?;
+                Ok(response)
+            }
+            Mode::Replaying(responses) => {
+                let response = {
+                    let mut responses = responses.lock().expect("cassette poisoned");
+                    let queue = responses.get_mut(&request).ok_or_else(|| {
+                        anyhow::anyhow!("no recorded response for {request:?} in the cassette")
+                    })?;
+                    if queue.len() > 1 {
+                        queue.pop_front()
+                    } else {
+                        queue.front().cloned()
+                    }
+                    .expect("recorded requests have at least one response")
+                };
+                Ok(serde_json::from_value(response)?)
+            }
+        }
+    }
+}
+
+impl<P> CassetteProvider<P> {
+    /// Whether the provider replays a cassette, without network access.
+    #[must_use]
+    pub const fn is_replaying(&self) -> bool {
+        matches!(self.mode, Mode::Replaying(_))
+    }
+
+    /// Writes the interactions recorded so far to the cassette. A no-op unless recording.
+    ///
+    /// # Errors
+    /// Returns an error if the cassette cannot be written.
+    pub fn flush(&self) -> Result<()> {
+        if let Mode::Recording { path, cassette, .. } = &self.mode {
+            write_cassette(path, &cassette.lock().expect("cassette poisoned"))?;
+        }
+        Ok(())
+    }
+}
+
+impl<P> Drop for CassetteProvider<P> {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            log::error!("{e:#}");
+        }
+    }
+}
+
+impl CassetteProvider<FailoverProvider<HttpProvider>> {
+    /// Creates a new provider from the environment variables:
+    /// - `TENDERMINT_RPC_CASSETTE`: the path of the cassette. Without it, nothing is recorded.
+    /// - `TENDERMINT_RPC_CASSETTE_MODE`: `replay` (default) or `record`.
+    ///
+    /// The endpoints are only read from the environment when not replaying, see
+    /// [`FailoverProvider::from_env`].
+    ///
+    /// # Panics
+    /// Panics if the mode is invalid, if the cassette cannot be replayed, or if the endpoints
+    /// are invalid.
+    #[must_use]
+    pub fn from_env() -> Self {
+        let Ok(path) = env::var("TENDERMINT_RPC_CASSETTE") else {
+            return Self::disabled(FailoverProvider::from_env());
+        };
+        match env::var("TENDERMINT_RPC_CASSETTE_MODE")
+            .as_deref()
+            .unwrap_or("replay")
+        {
+            "replay" => Self::replay(Path::new(&path)).expect("Failed to replay the cassette"),
+            "record" => Self::record(FailoverProvider::from_env(), path),
+            mode => panic!("invalid TENDERMINT_RPC_CASSETTE_MODE '{mode}'"),
+        }
+    }
+}
+
+/// Writes `cassette` to `path`, see [`write_atomically`].
+fn write_cassette(path: &Path, cassette: &Cassette) -> Result<()> {
+    let contents = serde_json::to_string_pretty(cassette)?;
+    write_atomically(path, &contents)
+        .with_context(|| format!("failed to write cassette {}", path.display()))
+}
+
+/// Writes `contents` to a temporary file and renames it to `path`, so that an interrupted write
+/// does not corrupt the cassette.
+fn write_atomically(path: &Path, contents: &str) -> Result<()> {
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, contents)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[async_trait]
+impl<P: LightBlockProvider> LightBlockProvider for CassetteProvider<P> {
+    fn trust(&self, trusted_light_block: &LightBlock, options: &Options) {
+        match &self.mode {
+            Mode::Disabled(inner) | Mode::Recording { inner, .. } => {
+                inner.trust(trusted_light_block, options);
+            }
+            Mode::Replaying(_) => {}
+        }
+    }
+
+    fn pin(&self) {
+        match &self.mode {
+            Mode::Disabled(inner) | Mode::Recording { inner, .. } => inner.pin(),
+            Mode::Replaying(_) => {}
+        }
+    }
+
+    fn unpin(&self) {
+        match &self.mode {
+            Mode::Disabled(inner) | Mode::Recording { inner, .. } => inner.unpin(),
+            Mode::Replaying(_) => {}
+        }
+    }
+
+    async fn peer_id(&self) -> Result<PeerId> {
+        self.call(Request::PeerId, |inner| inner.peer_id()).await
+    }
+
+    async fn latest_height(&self) -> Result<u32> {
+        self.call(Request::LatestHeight, |inner| inner.latest_height())
+            .await
+    }
+
+    async fn signed_header(&self, height: u32) -> Result<SignedHeader> {
+        self.call(Request::SignedHeader { height }, |inner| {
+            inner.signed_header(height)
+        })
+        .await
+    }
+
+    async fn validators(&self, height: u32) -> Result<ValidatorSet> {
+        self.call(Request::Validators { height }, |inner| {
+            inner.validators(height)
+        })
+        .await
+    }
+
+    async fn abci_query(
+        &self,
+        path: Option<String>,
+        data: Vec<u8>,
+        height: Option<u32>,
+        prove: bool,
+    ) -> Result<AbciQuery> {
+        let request = Request::AbciQuery {
+            path: path.clone(),
+            data: data.clone(),
+            height,
+            prove,
+        };
+        self.call(request, |inner| inner.abci_query(path, data, height, prove))
+            .await
+    }
+
+    async fn light_block(&self, height: u32) -> Result<LightBlock> {
+        self.call(Request::LightBlock { height }, |inner| {
+            inner.light_block(height)
+        })
+        .await
+    }
+}
@@ -0,0 +1,27 @@
+//! Detection of the CometBFT RPC dialect of a node.
+
+use anyhow::{Context, Result};
+use tendermint_rpc::client::CompatMode;
+
+/// Gets the compatibility mode of the RPC of a CometBFT `version`, such as `0.34.27`, `v0.38` or
+/// `1.0.0`. Tendermint 0.34 has its own dialect, later versions share the dialect of 0.37.
+///
+/// # Errors
+/// Returns an error if the version cannot be parsed or is older than 0.34.
+pub fn compat_mode_from_version(version: &str) -> Result<CompatMode> {
+    let mut parts = version.trim().trim_start_matches('v').split('.');
+    let mut next_number = || -> Result<u64> {
+        parts
+            .next()
+            .and_then(|part| part.split(|c: char| !c.is_ascii_digit()).next())
+            .filter(|part| !part.is_empty())
+            .with_context(|| format!("invalid CometBFT version {version}"))?
+            .parse()
+            .with_context(|| format!("invalid CometBFT version {version}"))
+    };
+    match (next_number()?, next_number()?) {
+        (0, 34) => Ok(CompatMode::V0_34),
+        (0, 37..) | (1.., _) => Ok(CompatMode::V0_37),
+        _ => anyhow::bail!("unsupported CometBFT version {version}"),
+    }
+}
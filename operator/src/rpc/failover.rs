@@ -0,0 +1,266 @@
+//! A [`LightBlockProvider`] that fails over between multiple endpoints.
+
+use std::{
+    env,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use tendermint::{block::signed_header::SignedHeader, node::Id as PeerId};
+use tendermint_light_client_verifier::types::{LightBlock, ValidatorSet};
+use tendermint_rpc::endpoint::abci_query::AbciQuery;
+
+use super::{HttpProvider, LightBlockProvider, TransportOptions};
+
+/// The time an endpoint is skipped after its first failure. It doubles with every consecutive
+/// failure, up to [`MAX_COOLDOWN`].
+const BASE_COOLDOWN: Duration = Duration::from_secs(5);
+
+/// The maximum time an endpoint is skipped after consecutive failures.
+const MAX_COOLDOWN: Duration = Duration::from_secs(300);
+
+/// The order in which the endpoints are tried.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Selection {
+    /// Always try the endpoints in the given order.
+    #[default]
+    Priority,
+    /// Start with the next endpoint on every request, spreading the load.
+    RoundRobin,
+}
+
+impl FromStr for Selection {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "priority" => Ok(Self::Priority),
+            "round-robin" => Ok(Self::RoundRobin),
+            _ => anyhow::bail!("invalid selection '{s}', expected 'priority' or 'round-robin'"),
+        }
+    }
+}
+
+/// A [`LightBlockProvider`] that sends each request to one of multiple endpoints, failing over
+/// to the next endpoint on errors and timeouts. The requests to an endpoint are bounded by the
+/// timeout and retries of its own transport, see [`TransportOptions`].
+///
+/// An endpoint that fails is skipped for a cooldown that grows with its consecutive failures.
+/// The endpoints in cooldown are still tried last, so a request only fails if all the endpoints
+/// fail.
+///
+/// While pinned, see [`LightBlockProvider::pin`], the requests only go to the pinned endpoint and
+/// fail with it, since endpoints at different heights would serve inconsistent responses.
+pub struct FailoverProvider<P> {
+    endpoints: Vec<Endpoint<P>>,
+    selection: Selection,
+    next: AtomicUsize,
+    pinned: Mutex<Option<usize>>,
+}
+
+struct Endpoint<P> {
+    name: String,
+    provider: P,
+    health: Mutex<Health>,
+}
+
+#[derive(Default)]
+struct Health {
+    consecutive_failures: u32,
+    unhealthy_until: Option<Instant>,
+}
+
+impl<P: LightBlockProvider> FailoverProvider<P> {
+    /// Creates a new provider for the named `endpoints`, tried in the order of `selection`.
+    ///
+    /// # Errors
+    /// Returns an error if `endpoints` is empty.
+    pub fn new(endpoints: Vec<(String, P)>, selection: Selection) -> Result<Self> {
+        anyhow::ensure!(!endpoints.is_empty(), "no RPC endpoints given");
+        Ok(Self {
+            endpoints: endpoints
+                .into_iter()
+                .map(|(name, provider)| Endpoint {
+                    name,
+                    provider,
+                    health: Mutex::new(Health::default()),
+                })
+                .collect(),
+            selection,
+            next: AtomicUsize::new(0),
+            pinned: Mutex::new(None),
+        })
+    }
+
+    /// Gets the indices of the endpoints in the order they should be tried: the healthy ones in
+    /// the order of the selection, then the ones in cooldown.
+    fn order(&self) -> Vec<usize> {
+        let len = self.endpoints.len();
+        let start = match self.selection {
+            Selection::Priority => 0,
+            Selection::RoundRobin => self.next.fetch_add(1, Ordering::Relaxed) % len,
+        };
+        let (healthy, unhealthy): (Vec<_>, Vec<_>) = (0..len)
+            .map(|i| (start + i) % len)
+            .partition(|&i| self.endpoints[i].is_healthy());
+        healthy.into_iter().chain(unhealthy).collect()
+    }
+
+    /// Gets the endpoints a request should be tried on: the pinned one if any, otherwise all of
+    /// them in the order they should be tried.
+    fn candidates(&self) -> Vec<&Endpoint<P>> {
+        let pinned = *self.pinned.lock().expect("pinned endpoint poisoned");
+        match pinned {
+            Some(i) => vec![&self.endpoints[i]],
+            None => self
+                .order()
+                .into_iter()
+                .map(|i| &self.endpoints[i])
+                .collect(),
+        }
+    }
+
+    /// Performs `request` on the endpoints until one succeeds.
+    async fn call<T, F>(&self, method: &str, request: F) -> Result<T>
+    where
+        T: Send,
+        F: for<'a> Fn(&'a P) -> BoxFuture<'a, Result<T>> + Send + Sync,
+    {
+        let mut last_error = None;
+        for endpoint in self.candidates() {
+            match request(&endpoint.provider).await {
+                Ok(value) => {
+                    endpoint.record_success();
+                    return Ok(value);
+                }
+                Err(e) => {
+                    log::warn!("{method} request to {} failed: {e:#}", endpoint.name);
+                    endpoint.record_failure();
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error
+            .expect("there is at least one endpoint")
+            .context(format!("all RPC endpoints failed the {method} request")))
+    }
+}
+
+impl<P> Endpoint<P> {
+    fn is_healthy(&self) -> bool {
+        let unhealthy_until = self
+            .health
+            .lock()
+            .expect("endpoint health poisoned")
+            .unhealthy_until;
+        !unhealthy_until.is_some_and(|until| Instant::now() < until)
+    }
+
+    fn record_success(&self) {
+        let mut health = self.health.lock().expect("endpoint health poisoned");
+        if health.consecutive_failures > 0 {
+            log::info!("RPC endpoint {} recovered", self.name);
+        }
+        *health = Health::default();
+    }
+
+    fn record_failure(&self) {
+        let mut health = self.health.lock().expect("endpoint health poisoned");
+        health.consecutive_failures += 1;
+        let cooldown = BASE_COOLDOWN
+            .saturating_mul(1 << (health.consecutive_failures - 1).min(6))
+            .min(MAX_COOLDOWN);
+        health.unhealthy_until = Some(Instant::now() + cooldown);
+    }
+}
+
+impl FailoverProvider<HttpProvider> {
+    /// Creates a new provider from the environment variables:
+    /// - `TENDERMINT_RPC_URL`: a comma separated list of endpoints.
+    /// - `TENDERMINT_RPC_SELECTION`: `priority` (default) or `round-robin`.
+    ///
+    /// The endpoints use the transport options of the environment, see
+    /// [`TransportOptions::from_env`].
+    ///
+    /// # Panics
+    /// Panics if the `TENDERMINT_RPC_URL` environment variable is not set, if it has no valid
+    /// URLs, or if the selection or the transport options are invalid.
+    #[must_use]
+    pub fn from_env() -> Self {
+        let urls = env::var("TENDERMINT_RPC_URL").expect("TENDERMINT_RPC_URL not set");
+        let selection = env::var("TENDERMINT_RPC_SELECTION")
+            .ok()
+            .map(|selection| selection.parse().expect("invalid TENDERMINT_RPC_SELECTION"))
+            .unwrap_or_default();
+        let options = TransportOptions::from_env().expect("invalid RPC transport options");
+
+        let endpoints = urls
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(|url| {
+                let provider = HttpProvider::with_transport_options(url, options.clone())?;
+                Ok((url.to_string(), provider))
+            })
+            .collect::<Result<Vec<_>>>()
+            .expect("Failed to create HTTP client");
+        Self::new(endpoints, selection).expect("TENDERMINT_RPC_URL has no endpoints")
+    }
+}
+
+#[async_trait]
+impl<P: LightBlockProvider> LightBlockProvider for FailoverProvider<P> {
+    fn pin(&self) {
+        let first = self.order()[0];
+        log::debug!("Pinned RPC endpoint {}", self.endpoints[first].name);
+        *self.pinned.lock().expect("pinned endpoint poisoned") = Some(first);
+    }
+
+    fn unpin(&self) {
+        *self.pinned.lock().expect("pinned endpoint poisoned") = None;
+    }
+
+    async fn peer_id(&self) -> Result<PeerId> {
+        self.call("status", |provider| provider.peer_id()).await
+    }
+
+    async fn latest_height(&self) -> Result<u32> {
+        self.call("status", |provider| provider.latest_height())
+            .await
+    }
+
+    async fn signed_header(&self, height: u32) -> Result<SignedHeader> {
+        self.call("commit", |provider| provider.signed_header(height))
+            .await
+    }
+
+    async fn validators(&self, height: u32) -> Result<ValidatorSet> {
+        self.call("validators", |provider| provider.validators(height))
+            .await
+    }
+
+    async fn abci_query(
+        &self,
+        path: Option<String>,
+        data: Vec<u8>,
+        height: Option<u32>,
+        prove: bool,
+    ) -> Result<AbciQuery> {
+        self.call("abci_query", |provider| {
+            provider.abci_query(path.clone(), data.clone(), height, prove)
+        })
+        .await
+    }
+
+    async fn light_block(&self, height: u32) -> Result<LightBlock> {
+        self.call("light block", |provider| provider.light_block(height))
+            .await
+    }
+}
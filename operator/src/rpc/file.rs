@@ -0,0 +1,107 @@
+//! Light blocks saved to files, for generating fixtures and genesis without network access.
+
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use cosmos_sdk_proto::{cosmos::staking::v1beta1::Params, prost::Message, traits::MessageExt};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use tendermint_light_client_verifier::types::LightBlock;
+use tendermint_rpc::endpoint::abci_query::AbciQuery;
+
+use super::InMemoryProvider;
+
+/// A light block saved to a file, with the chain data the runners query at its height.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LightBlockFile {
+    /// The light block: the signed header, the validators and the next validators.
+    pub light_block: LightBlock,
+    /// The protobuf encoded Cosmos SDK staking parameters, if the chain has them.
+    #[serde_as(as = "Option<serde_with::hex::Hex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub staking_params: Option<Vec<u8>>,
+    /// The responses of the ABCI queries made at this light block.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub abci_queries: Vec<SavedAbciQuery>,
+}
+
+/// An ABCI query and its response.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedAbciQuery {
+    /// The query path.
+    pub path: Option<String>,
+    /// The query data.
+    #[serde_as(as = "serde_with::hex::Hex")]
+    pub data: Vec<u8>,
+    /// The query height.
+    pub height: Option<u32>,
+    /// The response of the query.
+    pub response: AbciQuery,
+}
+
+impl LightBlockFile {
+    /// Creates a new light block file.
+    ///
+    /// # Errors
+    /// Returns an error if the staking parameters cannot be encoded.
+    pub fn new(
+        light_block: LightBlock,
+        staking_params: Option<Params>,
+        abci_queries: Vec<SavedAbciQuery>,
+    ) -> Result<Self> {
+        Ok(Self {
+            light_block,
+            staking_params: staking_params.map(|params| params.to_bytes()).transpose()?,
+            abci_queries,
+        })
+    }
+
+    /// Reads a light block file.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn read(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read light block file {}", path.display()))?;
+        serde_json::from_str(&contents)
+            .with_context(|| format!("failed to parse light block file {}", path.display()))
+    }
+
+    /// Decodes the staking parameters, if any.
+    ///
+    /// # Errors
+    /// Returns an error if the staking parameters cannot be decoded.
+    pub fn staking_params(&self) -> Result<Option<Params>> {
+        Ok(self
+            .staking_params
+            .as_deref()
+            .map(Params::decode)
+            .transpose()?)
+    }
+}
+
+impl InMemoryProvider {
+    /// Creates a new provider serving the light blocks, staking parameters and ABCI queries of
+    /// the light block files at `paths`.
+    ///
+    /// # Errors
+    /// Returns an error if a file cannot be read or parsed.
+    pub fn from_light_block_files(paths: &[impl AsRef<Path>]) -> Result<Self> {
+        let mut provider = Self::default();
+        for path in paths {
+            let file = LightBlockFile::read(path.as_ref())?;
+            if let Some(params) = file.staking_params()? {
+                provider.insert_staking_params(params)?;
+            }
+            for query in file.abci_queries {
+                provider.insert_abci_query(query.path, query.data, query.height, query.response);
+            }
+            provider.insert_light_block(file.light_block)?;
+        }
+        Ok(provider)
+    }
+}
@@ -0,0 +1,212 @@
+//! A [`LightBlockProvider`] for a Tendermint node over JSON-RPC.
+
+use std::env;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use tendermint::{block::signed_header::SignedHeader, node::Id as PeerId, validator::Set};
+use tendermint_light_client_verifier::types::{LightBlock, ValidatorSet};
+use tendermint_rpc::endpoint::{
+    abci_query::{self, AbciQuery},
+    commit, status, validators,
+};
+
+use super::{LightBlockProvider, Transport, TransportOptions};
+
+/// The number of validators requested per page in batched requests, the maximum of the RPC.
+const VALIDATORS_PER_PAGE: usize = 100;
+
+/// A [`LightBlockProvider`] for a Tendermint node over JSON-RPC.
+///
+/// The requests go through a [`Transport`], and the requests of a light block are sent in a
+/// single JSON-RPC batch. Their responses are the same in all the RPC dialects.
+#[derive(Clone, Debug)]
+pub struct HttpProvider {
+    transport: Transport,
+}
+
+impl HttpProvider {
+    /// Creates a new provider for the node at `url`, with the default transport options.
+    ///
+    /// # Errors
+    /// Returns an error if the URL is invalid.
+    pub fn new(url: &str) -> Result<Self> {
+        Self::with_transport_options(url, TransportOptions::default())
+    }
+
+    /// Creates a new provider for the node at `url` with the transport `options`.
+    ///
+    /// # Errors
+    /// Returns an error if the URL is invalid.
+    pub fn with_transport_options(url: &str, options: TransportOptions) -> Result<Self> {
+        Ok(Self {
+            transport: Transport::new(url, options)?,
+        })
+    }
+
+    /// Creates a new provider from the `TENDERMINT_RPC_URL` environment variable, with the
+    /// transport options of the environment. See [`TransportOptions::from_env`].
+    ///
+    /// # Panics
+    /// Panics if the `TENDERMINT_RPC_URL` environment variable is not set or if the URL or the
+    /// transport options are invalid.
+    #[must_use]
+    pub fn from_env() -> Self {
+        Self::with_transport_options(
+            &env::var("TENDERMINT_RPC_URL").expect("TENDERMINT_RPC_URL not set"),
+            TransportOptions::from_env().expect("invalid RPC transport options"),
+        )
+        .expect("Failed to create HTTP client")
+    }
+
+    /// Sends the `(method, params)` requests in a single JSON-RPC batch and returns the results
+    /// in the order of the requests.
+    async fn batch(&self, requests: &[(&str, Value)]) -> Result<Vec<Value>> {
+        let body: Vec<Value> = requests
+            .iter()
+            .enumerate()
+            .map(|(id, (method, params))| {
+                json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+            })
+            .collect();
+
+        let responses: Vec<Value> =
+            serde_json::from_value(self.transport.post(&Value::Array(body)).await?)?;
+
+        let mut results = vec![Value::Null; requests.len()];
+        for mut response in responses {
+            let id = response
+                .get("id")
+                .and_then(Value::as_u64)
+                .and_then(|id| usize::try_from(id).ok())
+                .filter(|id| *id < requests.len())
+                .ok_or_else(|| anyhow::anyhow!("unexpected id in batch response: {response}"))?;
+            if let Some(error) = response.get("error") {
+                anyhow::bail!("{} request failed: {error}", requests[id].0);
+            }
+            results[id] = response
+                .get_mut("result")
+                .map(Value::take)
+                .ok_or_else(|| anyhow::anyhow!("{} response has no result", requests[id].0))?;
+        }
+        Ok(results)
+    }
+
+    /// Sends a single `method` request and decodes its result.
+    async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        let [result]: [Value; 1] = self
+            .batch(&[(method, params)])
+            .await?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("unexpected number of batch results"))?;
+        Ok(serde_json::from_value(result)?)
+    }
+
+    /// Completes the first page of validators at `height`, fetching the remaining pages if any.
+    async fn complete_validators(
+        &self,
+        height: u32,
+        first_page: validators::Response,
+    ) -> Result<ValidatorSet> {
+        if usize::try_from(first_page.total)? <= first_page.validators.len() {
+            return Ok(Set::new(first_page.validators, None));
+        }
+        self.validators(height).await
+    }
+}
+
+fn validators_request(height: u32, page: usize) -> (&'static str, Value) {
+    (
+        "validators",
+        json!({
+            "height": height.to_string(),
+            "page": page.to_string(),
+            "per_page": VALIDATORS_PER_PAGE.to_string(),
+        }),
+    )
+}
+
+#[async_trait]
+impl LightBlockProvider for HttpProvider {
+    async fn peer_id(&self) -> Result<PeerId> {
+        let status: status::Response = self.call("status", json!({})).await?;
+        Ok(status.node_info.id)
+    }
+
+    async fn latest_height(&self) -> Result<u32> {
+        let status: status::Response = self.call("status", json!({})).await?;
+        Ok(status.sync_info.latest_block_height.value().try_into()?)
+    }
+
+    async fn signed_header(&self, height: u32) -> Result<SignedHeader> {
+        let commit: commit::Response = self
+            .call("commit", json!({ "height": height.to_string() }))
+            .await?;
+        Ok(commit.signed_header)
+    }
+
+    async fn validators(&self, height: u32) -> Result<ValidatorSet> {
+        let mut validators = Vec::new();
+        for page in 1.. {
+            let (method, params) = validators_request(height, page);
+            let response: validators::Response = self.call(method, params).await?;
+            let last_page = response.validators.is_empty();
+            validators.extend(response.validators);
+            if last_page || validators.len() >= usize::try_from(response.total)? {
+                break;
+            }
+        }
+        Ok(Set::new(validators, None))
+    }
+
+    async fn abci_query(
+        &self,
+        path: Option<String>,
+        data: Vec<u8>,
+        height: Option<u32>,
+        prove: bool,
+    ) -> Result<AbciQuery> {
+        let mut params = json!({
+            "path": path.unwrap_or_default(),
+            "data": hex::encode(data),
+            "prove": prove,
+        });
+        if let Some(height) = height {
+            params["height"] = json!(height.to_string());
+        }
+        let response: abci_query::Response = self.call("abci_query", params).await?;
+        Ok(response.response)
+    }
+
+    async fn light_block(&self, height: u32) -> Result<LightBlock> {
+        let results = self
+            .batch(&[
+                ("status", json!({})),
+                ("commit", json!({ "height": height.to_string() })),
+                validators_request(height, 1),
+                validators_request(height + 1, 1),
+            ])
+            .await?;
+        let [status, commit, validators, next_validators]: [Value; 4] = results
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("unexpected number of batch results"))?;
+
+        let status: status::Response = serde_json::from_value(status)?;
+        let commit: commit::Response = serde_json::from_value(commit)?;
+        let validators = self
+            .complete_validators(height, serde_json::from_value(validators)?)
+            .await?;
+        let next_validators = self
+            .complete_validators(height + 1, serde_json::from_value(next_validators)?)
+            .await?;
+
+        Ok(LightBlock::new(
+            commit.signed_header,
+            validators,
+            next_validators,
+            status.node_info.id,
+        ))
+    }
+}
@@ -0,0 +1,142 @@
+//! A [`LightBlockProvider`] serving light blocks and ABCI queries from memory.
+
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use cosmos_sdk_proto::{
+    cosmos::staking::v1beta1::{Params, QueryParamsRequest, QueryParamsResponse},
+    traits::MessageExt,
+};
+use tendermint::{block::signed_header::SignedHeader, node::Id as PeerId};
+use tendermint_light_client_verifier::types::{LightBlock, ValidatorSet};
+use tendermint_rpc::endpoint::abci_query::AbciQuery;
+
+use super::{LightBlockProvider, STAKING_PARAMS_PATH};
+
+/// The key of an ABCI query: the path, the data and the height.
+type AbciQueryKey = (Option<String>, Vec<u8>, Option<u32>);
+
+/// A [`LightBlockProvider`] serving light blocks and ABCI queries from memory.
+///
+/// The validators at a height are taken from the light block at that height, or from the next
+/// validators of the light block at the previous height.
+#[derive(Clone, Debug, Default)]
+pub struct InMemoryProvider {
+    light_blocks: BTreeMap<u32, LightBlock>,
+    abci_queries: HashMap<AbciQueryKey, AbciQuery>,
+}
+
+impl InMemoryProvider {
+    /// Creates a new provider serving `light_blocks`.
+    ///
+    /// # Errors
+    /// Returns an error if the height of a light block does not fit in a `u32`.
+    pub fn new(light_blocks: impl IntoIterator<Item = LightBlock>) -> Result<Self> {
+        let mut provider = Self::default();
+        for light_block in light_blocks {
+            provider.insert_light_block(light_block)?;
+        }
+        Ok(provider)
+    }
+
+    /// Adds a light block, replacing any light block at the same height.
+    ///
+    /// # Errors
+    /// Returns an error if the height of the light block does not fit in a `u32`.
+    pub fn insert_light_block(&mut self, light_block: LightBlock) -> Result<()> {
+        let height = light_block.height().value().try_into()?;
+        self.light_blocks.insert(height, light_block);
+        Ok(())
+    }
+
+    /// Adds the response of the ABCI query of `data` at `path` and `height`. The `prove` flag of
+    /// the queries is ignored.
+    pub fn insert_abci_query(
+        &mut self,
+        path: Option<String>,
+        data: Vec<u8>,
+        height: Option<u32>,
+        response: AbciQuery,
+    ) {
+        self.abci_queries.insert((path, data, height), response);
+    }
+
+    /// Adds the response of the staking parameters query.
+    ///
+    /// # Errors
+    /// Returns an error if the query or the parameters cannot be encoded.
+    pub fn insert_staking_params(&mut self, params: Params) -> Result<()> {
+        let response = AbciQuery {
+            value: QueryParamsResponse {
+                params: Some(params),
+            }
+            .to_bytes()?,
+            ..Default::default()
+        };
+        self.insert_abci_query(
+            Some(STAKING_PARAMS_PATH.to_string()),
+            QueryParamsRequest::default().to_bytes()?,
+            None,
+            response,
+        );
+        Ok(())
+    }
+
+    fn get(&self, height: u32) -> Result<&LightBlock> {
+        self.light_blocks
+            .get(&height)
+            .ok_or_else(|| anyhow::anyhow!("no light block at height {height}"))
+    }
+}
+
+#[async_trait]
+impl LightBlockProvider for InMemoryProvider {
+    async fn peer_id(&self) -> Result<PeerId> {
+        self.light_blocks
+            .values()
+            .next_back()
+            .map(|light_block| light_block.provider)
+            .ok_or_else(|| anyhow::anyhow!("no light blocks"))
+    }
+
+    async fn latest_height(&self) -> Result<u32> {
+        self.light_blocks
+            .keys()
+            .next_back()
+            .copied()
+            .ok_or_else(|| anyhow::anyhow!("no light blocks"))
+    }
+
+    async fn signed_header(&self, height: u32) -> Result<SignedHeader> {
+        Ok(self.get(height)?.signed_header.clone())
+    }
+
+    async fn validators(&self, height: u32) -> Result<ValidatorSet> {
+        if let Some(light_block) = self.light_blocks.get(&height) {
+            return Ok(light_block.validators.clone());
+        }
+        height
+            .checked_sub(1)
+            .and_then(|previous| self.light_blocks.get(&previous))
+            .map(|light_block| light_block.next_validators.clone())
+            .ok_or_else(|| anyhow::anyhow!("no validators at height {height}"))
+    }
+
+    async fn abci_query(
+        &self,
+        path: Option<String>,
+        data: Vec<u8>,
+        height: Option<u32>,
+        _prove: bool,
+    ) -> Result<AbciQuery> {
+        self.abci_queries
+            .get(&(path, data, height))
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("no response for the ABCI query at height {height:?}"))
+    }
+
+    async fn light_block(&self, height: u32) -> Result<LightBlock> {
+        Ok(self.get(height)?.clone())
+    }
+}
@@ -0,0 +1,176 @@
+//! RPC clients for interacting with a Tendermint node.
+//!
+//! The runners fetch everything they need from the chain through a [`LightBlockProvider`]:
+//! - [`HttpProvider`] talks to a node over JSON-RPC, batching the requests of a light block. Its
+//!   [`Transport`] has timeouts, retries, a concurrency limit and custom headers or basic auth.
+//! - [`FailoverProvider`] spreads the requests over multiple endpoints and fails over between
+//!   them.
+//! - [`CachingProvider`] wraps another provider and reuses the headers and validator sets it
+//!   already fetched.
+//! - [`WitnessedProvider`] cross-checks the headers of another provider against witnesses and
+//!   fails with [`ForkDetected`] when they diverge.
+//! - [`CassetteProvider`] records the requests and responses of another provider to a cassette
+//!   file, or replays a cassette without network access.
+//! - [`InMemoryProvider`] serves light blocks and ABCI queries from memory, for offline use,
+//!   for example from [`LightBlockFile`]s.
+//! - A synthetic `TestChain` serves its own light blocks, staking parameters and store proofs.
+//!
+//! [`MockRpcServer`] serves any provider over the subset of the CometBFT RPC used by the operator.
+//!
+//! [`NewBlocks`] notifies the operator of new blocks through a websocket subscription.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use cosmos_sdk_proto::{
+    cosmos::staking::v1beta1::{Params, QueryParamsRequest, QueryParamsResponse},
+    prost::Message,
+    traits::MessageExt,
+};
+use ibc_core_commitment_types::merkle::MerkleProof;
+use tendermint::block::signed_header::SignedHeader;
+use tendermint_light_client_verifier::types::{LightBlock, ValidatorSet};
+
+mod caching;
+mod cassette;
+mod compat;
+mod failover;
+mod file;
+mod http;
+mod memory;
+mod proof;
+mod provider;
+mod server;
+mod subscription;
+mod synthetic;
+mod transport;
+mod witness;
+
+pub use caching::CachingProvider;
+pub use cassette::CassetteProvider;
+pub use compat::compat_mode_from_version;
+pub use failover::{FailoverProvider, Selection};
+pub use file::{LightBlockFile, SavedAbciQuery};
+pub use http::HttpProvider;
+pub use memory::InMemoryProvider;
+pub use proof::{verify_kv_response, KvProofError, IBC_STORE};
+pub use provider::LightBlockProvider;
+pub use server::MockRpcServer;
+pub use subscription::NewBlocks;
+pub use transport::{Transport, TransportOptions};
+pub use witness::{ForkDetected, MisbehaviourEvidence, WitnessedProvider};
+
+/// The ABCI query path of the Cosmos SDK staking parameters.
+pub const STAKING_PARAMS_PATH: &str = "/cosmos.staking.v1beta1.Query/Params";
+
+/// An extension trait for [`LightBlockProvider`] that provides additional methods for
+/// obtaining light blocks.
+#[async_trait]
+pub trait TendermintRpcExt: LightBlockProvider {
+    /// Gets a light block for a specific block height, with the signatures sorted by voting
+    /// power. If `block_height` is `None`, the latest block is fetched.
+    ///
+    /// # Errors
+    /// Returns an error if the RPC request fails or if the response cannot be parsed.
+    async fn get_light_block(&self, block_height: Option<u32>) -> Result<LightBlock>;
+    /// Queries the Cosmos SDK for staking parameters.
+    ///
+    /// # Errors
+    /// Returns an error if the query fails or if the response cannot be decoded.
+    async fn sdk_staking_params(&self) -> Result<Params>;
+    /// Queries the proof of `key` in `store` at `proof_height`, and verifies it against the app
+    /// hash of the block at `proof_height + 1`. Returns the key, the proof and the value, which
+    /// is empty for a non-membership proof.
+    ///
+    /// # Errors
+    /// Returns an error if the query fails, or a [`KvProofError`] if the response is rejected.
+    async fn prove_kv(
+        &self,
+        store: &str,
+        key: &str,
+        proof_height: u32,
+    ) -> Result<(String, MerkleProof, Vec<u8>)>;
+}
+
+#[async_trait]
+impl<T: LightBlockProvider + ?Sized> TendermintRpcExt for T {
+    async fn get_light_block(&self, block_height: Option<u32>) -> Result<LightBlock> {
+        let height = match block_height {
+            Some(height) => height,
+            None => self.latest_height().await?,
+        };
+
+        let mut light_block = self.light_block(height).await?;
+        sort_signatures_by_validators_power_desc(
+            &mut light_block.signed_header,
+            &light_block.validators,
+        );
+        Ok(light_block)
+    }
+
+    async fn sdk_staking_params(&self) -> Result<Params> {
+        let abci_resp = self
+            .abci_query(
+                Some(STAKING_PARAMS_PATH.to_string()),
+                QueryParamsRequest::default().to_bytes()?,
+                None,
+                false,
+            )
+            .await?;
+        QueryParamsResponse::decode(abci_resp.value.as_slice())?
+            .params
+            .ok_or_else(|| anyhow::anyhow!("No staking params found"))
+    }
+
+    async fn prove_kv(
+        &self,
+        store: &str,
+        key: &str,
+        proof_height: u32,
+    ) -> Result<(String, MerkleProof, Vec<u8>)> {
+        let response = self
+            .abci_query(
+                Some(format!("store/{store}/key")),
+                key.as_bytes().to_vec(),
+                Some(proof_height),
+                true,
+            )
+            .await?;
+        let app_hash = self.signed_header(proof_height + 1).await?.header.app_hash;
+
+        let (proof, value) =
+            verify_kv_response(store, key, proof_height, response, app_hash.as_bytes())
+                .with_context(|| {
+                    format!("invalid proof of {store}/{key} at height {proof_height}")
+                })?;
+        if value.is_empty() {
+            log::info!("Proving the non-membership of {store}/{key}");
+        }
+        Ok((key.to_string(), proof, value))
+    }
+}
+
+/// Sorts the signatures in the signed header based on the descending order of validators' power.
+fn sort_signatures_by_validators_power_desc(
+    signed_header: &mut SignedHeader,
+    validators_set: &ValidatorSet,
+) {
+    let validator_powers: HashMap<_, _> = validators_set
+        .validators()
+        .iter()
+        .map(|v| (v.address, v.power()))
+        .collect();
+
+    signed_header.commit.signatures.sort_by(|a, b| {
+        let power_a = a
+            .validator_address()
+            .and_then(|addr| validator_powers.get(&addr))
+            .unwrap_or(&0);
+        let power_b = b
+            .validator_address()
+            .and_then(|addr| validator_powers.get(&addr))
+            .unwrap_or(&0);
+        power_b.cmp(power_a)
+    });
+}
@@ -0,0 +1,137 @@
+//! Validation of the key-value proofs returned by ABCI queries.
+
+use std::fmt;
+
+use ibc_core_commitment_types::{
+    commitment::CommitmentRoot,
+    merkle::MerkleProof,
+    proto::{ics23::HostFunctionsManager, v1::MerklePath},
+    specs::ProofSpecs,
+};
+use sp1_ics07_tendermint_utils::convert_tm_to_ics_merkle_proof;
+use tendermint_rpc::endpoint::abci_query::AbciQuery;
+
+/// The name of the IBC store.
+pub const IBC_STORE: &str = "ibc";
+
+/// Why the proof of a key returned by an ABCI query was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KvProofError {
+    /// The node failed the query.
+    Query {
+        /// The ABCI error code.
+        code: u32,
+        /// The log of the query.
+        log: String,
+    },
+    /// The node answered at another height than the proof height.
+    Height {
+        /// The proof height.
+        expected: u32,
+        /// The height of the response.
+        actual: u64,
+    },
+    /// The node answered for another key.
+    Key {
+        /// The queried key.
+        expected: Vec<u8>,
+        /// The key of the response.
+        actual: Vec<u8>,
+    },
+    /// The node did not return a proof.
+    MissingProof,
+    /// The proof cannot be decoded or is empty.
+    InvalidProof(String),
+    /// The proof does not verify against the app hash of the next block.
+    Verification(String),
+}
+
+impl fmt::Display for KvProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Query { code, log } => write!(f, "query failed with code {code}: {log}"),
+            Self::Height { expected, actual } => {
+                write!(f, "response at height {actual} instead of {expected}")
+            }
+            Self::Key { expected, actual } => write!(
+                f,
+                "response for key {} instead of {}",
+                String::from_utf8_lossy(actual),
+                String::from_utf8_lossy(expected)
+            ),
+            Self::MissingProof => write!(f, "no proof returned"),
+            Self::InvalidProof(reason) => write!(f, "invalid proof: {reason}"),
+            Self::Verification(reason) => {
+                write!(f, "proof does not verify against the app hash: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for KvProofError {}
+
+/// Checks that `response` answers the query of `key` in `store` at `proof_height`, and verifies
+/// its proof against `app_hash`, the app hash of the block at `proof_height + 1`. An empty value
+/// is verified as a non-membership proof. Returns the proof and the value.
+///
+/// # Errors
+/// Returns the reason the response is rejected.
+pub fn verify_kv_response(
+    store: &str,
+    key: &str,
+    proof_height: u32,
+    response: AbciQuery,
+    app_hash: &[u8],
+) -> Result<(MerkleProof, Vec<u8>), KvProofError> {
+    if response.code.is_err() {
+        return Err(KvProofError::Query {
+            code: response.code.value(),
+            log: response.log,
+        });
+    }
+    if response.height.value() != u64::from(proof_height) {
+        return Err(KvProofError::Height {
+            expected: proof_height,
+            actual: response.height.value(),
+        });
+    }
+    if response.key != key.as_bytes() {
+        return Err(KvProofError::Key {
+            expected: key.as_bytes().to_vec(),
+            actual: response.key,
+        });
+    }
+
+    let proof = response.proof.ok_or(KvProofError::MissingProof)?;
+    let proof = convert_tm_to_ics_merkle_proof(&proof)
+        .map_err(|e| KvProofError::InvalidProof(e.to_string()))?;
+    if proof.proofs.is_empty() {
+        return Err(KvProofError::InvalidProof(
+            "no commitment proofs".to_string(),
+        ));
+    }
+
+    let root = CommitmentRoot::from_bytes(app_hash);
+    let path = MerklePath {
+        key_path: vec![store.to_string(), key.to_string()],
+    };
+    let value = response.value;
+    let verified = if value.is_empty() {
+        proof.verify_non_membership::<HostFunctionsManager>(
+            &ProofSpecs::cosmos(),
+            root.into(),
+            path,
+        )
+    } else {
+        proof.verify_membership::<HostFunctionsManager>(
+            &ProofSpecs::cosmos(),
+            root.into(),
+            path,
+            value.clone(),
+            0,
+        )
+    };
+    verified.map_err(|e| KvProofError::Verification(e.to_string()))?;
+
+    Ok((proof, value))
+}
@@ -0,0 +1,62 @@
+//! The [`LightBlockProvider`] trait.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use tendermint::{block::signed_header::SignedHeader, node::Id as PeerId};
+use tendermint_light_client_verifier::{
+    options::Options,
+    types::{LightBlock, ValidatorSet},
+};
+use tendermint_rpc::endpoint::abci_query::AbciQuery;
+
+/// A source of light blocks and ABCI queries of a Tendermint chain.
+#[async_trait]
+pub trait LightBlockProvider: Send + Sync {
+    /// Gets the identifier of the node serving the light blocks.
+    async fn peer_id(&self) -> Result<PeerId>;
+
+    /// Gets the latest block height.
+    async fn latest_height(&self) -> Result<u32>;
+
+    /// Gets the signed header at `height`.
+    async fn signed_header(&self, height: u32) -> Result<SignedHeader>;
+
+    /// Gets the validator set at `height`.
+    async fn validators(&self, height: u32) -> Result<ValidatorSet>;
+
+    /// Performs an ABCI query of `data` at `path`, at the latest height if `height` is `None`.
+    async fn abci_query(
+        &self,
+        path: Option<String>,
+        data: Vec<u8>,
+        height: Option<u32>,
+        prove: bool,
+    ) -> Result<AbciQuery>;
+
+    /// Sends the following requests to a single endpoint until [`Self::unpin`], so that they
+    /// see the same state of the chain. Pinning again picks the best endpoint anew. A no-op for
+    /// providers with a single endpoint.
+    fn pin(&self) {}
+
+    /// Lets the requests fail over between the endpoints again. See [`Self::pin`].
+    fn unpin(&self) {}
+
+    /// Sets the light block that the light blocks of the witnesses are verified from, with the
+    /// verification `options` of the client. A no-op for providers without witnesses.
+    fn trust(&self, _trusted_light_block: &LightBlock, _options: &Options) {}
+
+    /// Gets the light block at `height`. The signatures are in the order returned by the node,
+    /// see [`super::TendermintRpcExt::get_light_block`].
+    async fn light_block(&self, height: u32) -> Result<LightBlock> {
+        let signed_header = self.signed_header(height).await?;
+        let validators = self.validators(height).await?;
+        let next_validators = self.validators(height + 1).await?;
+        let peer_id = self.peer_id().await?;
+        Ok(LightBlock::new(
+            signed_header,
+            validators,
+            next_validators,
+            peer_id,
+        ))
+    }
+}
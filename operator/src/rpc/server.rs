@@ -0,0 +1,223 @@
+//! A mock CometBFT RPC server serving a [`LightBlockProvider`].
+
+use std::{net::SocketAddr, sync::Arc};
+
+use anyhow::{Context, Result};
+use axum::{extract::State, routing::post, Json, Router};
+use serde_json::{json, Value};
+use tendermint_rpc::endpoint::{abci_query, commit, validators};
+
+use super::LightBlockProvider;
+
+/// The JSON-RPC error code of unknown methods.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// The JSON-RPC error code of failed requests.
+const INTERNAL_ERROR: i64 = -32603;
+
+/// The default number of validators per page, as in CometBFT.
+const DEFAULT_PER_PAGE: usize = 30;
+
+/// The maximum number of validators per page, as in CometBFT.
+const MAX_PER_PAGE: usize = 100;
+
+/// A JSON-RPC server implementing the subset of the CometBFT RPC used by the operator:
+/// `status`, `commit`, `validators` and `abci_query`, in single and batch requests.
+///
+/// A height of 0 or no height stands for the latest height. The status reports the latest block
+/// as the earliest one.
+pub struct MockRpcServer {
+    provider: Box<dyn LightBlockProvider>,
+}
+
+impl MockRpcServer {
+    /// Create a new server serving the blocks and queries of `provider`.
+    #[must_use]
+    pub fn new(provider: Box<dyn LightBlockProvider>) -> Self {
+        Self { provider }
+    }
+
+    /// Serve the RPC on `addr`.
+    ///
+    /// # Errors
+    /// Returns an error if the address cannot be bound or the server fails.
+    pub async fn serve(self, addr: SocketAddr) -> Result<()> {
+        let app = Router::new()
+            .route("/", post(handle))
+            .with_state(Arc::new(self));
+
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        log::info!("Mock RPC listening on {addr}");
+        axum::serve(listener, app).await?;
+        Ok(())
+    }
+
+    /// Answers a single JSON-RPC request.
+    async fn call(&self, request: Value) -> Value {
+        let id = request.get("id").cloned().unwrap_or(Value::Null);
+        let method = request
+            .get("method")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let params = request.get("params").cloned().unwrap_or_else(|| json!({}));
+        log::debug!("{method} {params}");
+
+        let result = match method {
+            "status" => self.status().await,
+            "commit" => self.commit(&params).await,
+            "validators" => self.validators(&params).await,
+            "abci_query" => self.abci_query(&params).await,
+            _ => {
+                return error_response(
+                    id,
+                    METHOD_NOT_FOUND,
+                    "Method not found",
+                    format!("unknown method {method}"),
+                );
+            }
+        };
+        match result {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(e) => {
+                log::warn!("{method} {params} failed: {e:#}");
+                error_response(id, INTERNAL_ERROR, "Internal error", format!("{e:#}"))
+            }
+        }
+    }
+
+    async fn status(&self) -> Result<Value> {
+        let height = self.provider.latest_height().await?;
+        let header = self.provider.signed_header(height).await?.header;
+        let validators = self.provider.validators(height).await?;
+        let peer_id = self.provider.peer_id().await?;
+
+        let block_hash = hex::encode_upper(header.hash().as_bytes());
+        let app_hash = hex::encode_upper(header.app_hash.as_bytes());
+        let time = header.time.to_rfc3339();
+        Ok(json!({
+            "node_info": {
+                "protocol_version": {
+                    "p2p": "8",
+                    "block": header.version.block.to_string(),
+                    "app": header.version.app.to_string(),
+                },
+                "id": peer_id.to_string(),
+                "listen_addr": "tcp://0.0.0.0:26656",
+                "network": header.chain_id.to_string(),
+                "version": "0.37.0",
+                "channels": "40202122233038606100",
+                "moniker": "mock-rpc",
+                "other": { "tx_index": "off", "rpc_address": "tcp://0.0.0.0:26657" },
+            },
+            "sync_info": {
+                "latest_block_hash": block_hash,
+                "latest_app_hash": app_hash,
+                "latest_block_height": height.to_string(),
+                "latest_block_time": time,
+                "earliest_block_hash": block_hash,
+                "earliest_app_hash": app_hash,
+                "earliest_block_height": height.to_string(),
+                "earliest_block_time": time,
+                "catching_up": false,
+            },
+            "validator_info": validators.validators().first(),
+        }))
+    }
+
+    async fn commit(&self, params: &Value) -> Result<Value> {
+        let height = self.height(params).await?;
+        Ok(serde_json::to_value(commit::Response {
+            signed_header: self.provider.signed_header(height).await?,
+            canonical: true,
+        })?)
+    }
+
+    async fn validators(&self, params: &Value) -> Result<Value> {
+        let height = self.height(params).await?;
+        let validators = self.provider.validators(height).await?;
+        let validators = validators.validators();
+
+        let per_page = usize::try_from(u64_param(params, "per_page")?.unwrap_or(0))?;
+        let per_page = if per_page == 0 {
+            DEFAULT_PER_PAGE
+        } else {
+            per_page.min(MAX_PER_PAGE)
+        };
+        let pages = validators.len().div_ceil(per_page).max(1);
+        let page = usize::try_from(u64_param(params, "page")?.unwrap_or(1))?;
+        anyhow::ensure!(
+            (1..=pages).contains(&page),
+            "page should be within [1, {pages}] range, given {page}"
+        );
+
+        let start = (page - 1) * per_page;
+        let end = (start + per_page).min(validators.len());
+        Ok(serde_json::to_value(validators::Response::new(
+            height.into(),
+            validators[start..end].to_vec(),
+            validators.len().try_into()?,
+        ))?)
+    }
+
+    async fn abci_query(&self, params: &Value) -> Result<Value> {
+        let path = params
+            .get("path")
+            .and_then(Value::as_str)
+            .map(ToString::to_string);
+        let data = match params.get("data").and_then(Value::as_str) {
+            Some(data) => hex::decode(data).context("invalid hex data")?,
+            None => vec![],
+        };
+        let height = u64_param(params, "height")?
+            .map(u32::try_from)
+            .transpose()?;
+        let prove = params
+            .get("prove")
+            .and_then(Value::as_bool)
+            .unwrap_or_default();
+
+        let response = self.provider.abci_query(path, data, height, prove).await?;
+        Ok(serde_json::to_value(abci_query::Response { response })?)
+    }
+
+    /// Gets the height parameter, defaulting to the latest height.
+    async fn height(&self, params: &Value) -> Result<u32> {
+        match u64_param(params, "height")? {
+            Some(height) => Ok(height.try_into()?),
+            None => self.provider.latest_height().await,
+        }
+    }
+}
+
+async fn handle(State(server): State<Arc<MockRpcServer>>, Json(body): Json<Value>) -> Json<Value> {
+    Json(match body {
+        Value::Array(requests) => Value::Array(
+            futures::future::join_all(requests.into_iter().map(|request| server.call(request)))
+                .await,
+        ),
+        request => server.call(request).await,
+    })
+}
+
+/// Gets the numeric parameter `name`, given as a string or a number. A value of 0 is `None`.
+fn u64_param(params: &Value, name: &str) -> Result<Option<u64>> {
+    let value = match params.get(name) {
+        None | Some(Value::Null) => return Ok(None),
+        Some(Value::String(value)) => value
+            .parse()
+            .with_context(|| format!("invalid {name} {value}"))?,
+        Some(Value::Number(value)) => value
+            .as_u64()
+            .with_context(|| format!("invalid {name} {value}"))?,
+        Some(value) => anyhow::bail!("invalid {name} {value}"),
+    };
+    Ok((value != 0).then_some(value))
+}
+
+fn error_response(id: Value, code: i64, message: &str, data: String) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message, "data": data },
+    })
+}
@@ -0,0 +1,172 @@
+//! Notifications of new blocks, from a websocket subscription or by polling.
+
+use std::time::Duration;
+
+use anyhow::Result;
+use futures::StreamExt;
+use tendermint_rpc::{
+    client::CompatMode, query::EventType, Client, SubscriptionClient, WebSocketClient,
+    WebSocketClientUrl,
+};
+use tokio::{sync::watch, task::JoinHandle};
+
+use super::compat_mode_from_version;
+
+/// The delay before the first reconnection attempt. It doubles with every consecutive failure, up
+/// to [`MAX_RECONNECT_DELAY`].
+const BASE_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// The maximum delay between reconnection attempts.
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(60);
+
+/// Waits for new blocks on the chain.
+///
+/// With a websocket URL, a background task subscribes to the `NewBlock` events and reconnects
+/// with exponential backoff when the connection drops. The poll interval is the longest wait in
+/// any case, so updates fall back to polling while the subscription is down.
+pub struct NewBlocks {
+    poll_interval: Duration,
+    subscription: Option<(watch::Receiver<u64>, JoinHandle<()>)>,
+}
+
+impl NewBlocks {
+    /// Creates a notifier that only polls every `poll_interval`.
+    #[must_use]
+    pub const fn polling(poll_interval: Duration) -> Self {
+        Self {
+            poll_interval,
+            subscription: None,
+        }
+    }
+
+    /// Creates a notifier subscribed to the `NewBlock` events of the websocket endpoint at `url`,
+    /// for example `ws://localhost:26657/websocket`, in `compat_mode` or the one detected from
+    /// the node version. Falls back to polling every `poll_interval`.
+    ///
+    /// # Errors
+    /// Returns an error if the URL is not a valid websocket URL.
+    pub fn websocket(
+        url: &str,
+        poll_interval: Duration,
+        compat_mode: Option<CompatMode>,
+    ) -> Result<Self> {
+        let (sender, receiver) = watch::channel(0);
+        let task = tokio::spawn(subscribe(
+            url.to_string(),
+            url.parse()?,
+            compat_mode,
+            sender,
+        ));
+        Ok(Self {
+            poll_interval,
+            subscription: Some((receiver, task)),
+        })
+    }
+
+    /// Waits until a new block is produced, or at most the poll interval.
+    pub async fn next(&mut self) {
+        let Some((receiver, _)) = &mut self.subscription else {
+            log::debug!("sleeping for {:?}", self.poll_interval);
+            tokio::time::sleep(self.poll_interval).await;
+            return;
+        };
+
+        // Only wait for blocks produced from now on, the caller already has the latest block.
+        receiver.mark_unchanged();
+        if tokio::time::timeout(self.poll_interval, receiver.changed())
+            .await
+            .is_err()
+        {
+            log::debug!("no new block event in {:?}, polling", self.poll_interval);
+        }
+    }
+}
+
+impl Drop for NewBlocks {
+    fn drop(&mut self) {
+        if let Some((_, task)) = &self.subscription {
+            task.abort();
+        }
+    }
+}
+
+/// Keeps a `NewBlock` subscription to `url` alive, counting the events in `sender`.
+async fn subscribe(
+    name: String,
+    url: WebSocketClientUrl,
+    compat_mode: Option<CompatMode>,
+    sender: watch::Sender<u64>,
+) {
+    let mut failures = 0;
+    loop {
+        match forward_new_blocks(url.clone(), compat_mode, &sender).await {
+            Ok(()) => {
+                log::warn!("Websocket subscription to {name} closed");
+                failures = 0;
+            }
+            Err(e) => {
+                log::warn!("Websocket subscription to {name} failed: {e:#}");
+                failures += 1;
+            }
+        }
+        let delay = BASE_RECONNECT_DELAY
+            .saturating_mul(1 << failures.min(6))
+            .min(MAX_RECONNECT_DELAY);
+        log::info!("Reconnecting to {name} in {delay:?}");
+        tokio::time::sleep(delay).await;
+    }
+}
+
+/// Subscribes to the `NewBlock` events of `url` and counts them in `sender`, until the
+/// subscription ends.
+async fn forward_new_blocks(
+    url: WebSocketClientUrl,
+    compat_mode: Option<CompatMode>,
+    sender: &watch::Sender<u64>,
+) -> Result<()> {
+    let compat_mode = match compat_mode {
+        Some(compat_mode) => compat_mode,
+        None => detect_compat_mode(url.clone()).await?,
+    };
+    let (client, driver) = WebSocketClient::builder(url)
+        .compat_mode(compat_mode)
+        .build()
+        .await?;
+    let driver = tokio::spawn(driver.run());
+
+    let result = async {
+        let mut subscription = client.subscribe(EventType::NewBlock.into()).await?;
+        log::info!("Subscribed to new blocks");
+        while let Some(event) = subscription.next().await {
+            event?;
+            sender.send_modify(|count| *count += 1);
+        }
+        anyhow::Ok(())
+    }
+    .await;
+
+    // The driver is already gone if the connection dropped.
+    if let Err(e) = client.close() {
+        log::debug!("Failed to close the websocket client: {e}");
+    }
+    let driver_result = driver.await?;
+    result?;
+    Ok(driver_result?)
+}
+
+/// Detects the compatibility mode of the node at `url` from the version in its status, whose
+/// format is the same in all dialects.
+async fn detect_compat_mode(url: WebSocketClientUrl) -> Result<CompatMode> {
+    let (client, driver) = WebSocketClient::new(url).await?;
+    let driver = tokio::spawn(driver.run());
+    let status = client.status().await;
+    if let Err(e) = client.close() {
+        log::debug!("Failed to close the websocket client: {e}");
+    }
+    driver.await??;
+
+    let version = status?.node_info.version.to_string();
+    let compat_mode = compat_mode_from_version(&version)?;
+    log::info!("Detected CometBFT {version}, subscribing with {compat_mode:?}");
+    Ok(compat_mode)
+}
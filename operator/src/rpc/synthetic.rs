@@ -0,0 +1,192 @@
+//! A [`LightBlockProvider`] serving a synthetic chain.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use cosmos_sdk_proto::{
+    cosmos::staking::v1beta1::{Params, QueryParamsResponse},
+    tendermint::google::protobuf::Duration,
+    traits::MessageExt,
+};
+use sp1_ics07_tendermint_testgen::TestChain;
+use tendermint::{block::signed_header::SignedHeader, node::Id as PeerId};
+use tendermint_light_client_verifier::types::{LightBlock, ValidatorSet};
+use tendermint_rpc::endpoint::abci_query::AbciQuery;
+
+use super::{LightBlockProvider, STAKING_PARAMS_PATH};
+
+/// The ABCI query path of the keys of the IBC store.
+const IBC_STORE_KEY_PATH: &str = "store/ibc/key";
+
+/// Serves the light blocks of the chain, its staking parameters with its unbonding period, and
+/// the keys of its IBC store with proofs.
+#[async_trait]
+impl LightBlockProvider for TestChain {
+    async fn peer_id(&self) -> Result<PeerId> {
+        Ok(self
+            .light_block(self.latest_height())
+            .ok_or_else(|| anyhow::anyhow!("no blocks"))?
+            .provider)
+    }
+
+    async fn latest_height(&self) -> Result<u32> {
+        Ok(Self::latest_height(self))
+    }
+
+    async fn signed_header(&self, height: u32) -> Result<SignedHeader> {
+        Ok(self
+            .light_block(height)
+            .ok_or_else(|| no_block(height))?
+            .signed_header
+            .clone())
+    }
+
+    async fn validators(&self, height: u32) -> Result<ValidatorSet> {
+        if let Some(light_block) = self.light_block(height) {
+            return Ok(light_block.validators.clone());
+        }
+        // The validators of the next block are known from the latest block.
+        height
+            .checked_sub(1)
+            .and_then(|previous| self.light_block(previous))
+            .map(|light_block| light_block.next_validators.clone())
+            .ok_or_else(|| no_block(height))
+    }
+
+    async fn abci_query(
+        &self,
+        path: Option<String>,
+        data: Vec<u8>,
+        height: Option<u32>,
+        prove: bool,
+    ) -> Result<AbciQuery> {
+        let height = height.unwrap_or_else(|| Self::latest_height(self));
+        match path.as_deref() {
+            Some(STAKING_PARAMS_PATH) => Ok(AbciQuery {
+                value: QueryParamsResponse {
+                    params: Some(Params {
+                        unbonding_time: Some(Duration {
+                            seconds: self.unbonding_period().as_secs().try_into()?,
+                            nanos: 0,
+                        }),
+                        ..Default::default()
+                    }),
+                }
+                .to_bytes()?,
+                height: height.into(),
+                ..Default::default()
+            }),
+            Some(IBC_STORE_KEY_PATH) => {
+                let (value, proof) = self.prove_ops(&data, height)?;
+                Ok(AbciQuery {
+                    key: data,
+                    value,
+                    proof: prove.then_some(proof),
+                    height: height.into(),
+                    ..Default::default()
+                })
+            }
+            _ => anyhow::bail!("unsupported ABCI query path {path:?}"),
+        }
+    }
+
+    async fn light_block(&self, height: u32) -> Result<LightBlock> {
+        self.light_block(height)
+            .cloned()
+            .ok_or_else(|| no_block(height))
+    }
+}
+
+fn no_block(height: u32) -> anyhow::Error {
+    anyhow::anyhow!("no synthetic block at height {height}")
+}
+
+#[cfg(test)]
+mod tests {
+    use sp1_ics07_tendermint_testgen::TestValidator;
+
+    use super::*;
+    use crate::rpc::{verify_kv_response, KvProofError, IBC_STORE};
+
+    async fn query_and_verify(chain: &TestChain, key: &str, height: u32) -> Vec<u8> {
+        let response = chain
+            .abci_query(
+                Some(IBC_STORE_KEY_PATH.to_string()),
+                key.as_bytes().to_vec(),
+                Some(height),
+                true,
+            )
+            .await
+            .unwrap();
+        let app_hash = &chain
+            .light_block(height + 1)
+            .unwrap()
+            .signed_header
+            .header
+            .app_hash;
+        let (_, value) =
+            verify_kv_response(IBC_STORE, key, height, response, app_hash.as_bytes()).unwrap();
+        value
+    }
+
+    #[tokio::test]
+    async fn store_proofs_pass_verify_kv_response() {
+        let mut chain =
+            TestChain::new("testgen-1", vec![TestValidator::new("alice", 100)]).unwrap();
+        chain
+            .store_mut()
+            .set("clients/07-tendermint-0/clientState", vec![1]);
+        chain
+            .store_mut()
+            .set("clients/07-tendermint-2/clientState", vec![2]);
+        chain.produce_blocks(2).unwrap();
+        chain
+            .store_mut()
+            .remove(b"clients/07-tendermint-2/clientState");
+        chain.produce_blocks(2).unwrap();
+
+        assert_eq!(
+            query_and_verify(&chain, "clients/07-tendermint-2/clientState", 1).await,
+            vec![2]
+        );
+        assert_eq!(
+            query_and_verify(&chain, "clients/07-tendermint-0/clientState", 3).await,
+            vec![1]
+        );
+        for key in [
+            "clients/07-tendermint-1/clientState",
+            "clients/07-tendermint-2/clientState",
+        ] {
+            assert!(query_and_verify(&chain, key, 3).await.is_empty(), "{key}");
+        }
+    }
+
+    #[tokio::test]
+    async fn a_proof_against_another_app_hash_is_rejected() {
+        let mut chain =
+            TestChain::new("testgen-1", vec![TestValidator::new("alice", 100)]).unwrap();
+        chain
+            .store_mut()
+            .set("clients/07-tendermint-0/clientState", vec![1]);
+        chain.produce_blocks(2).unwrap();
+        chain
+            .store_mut()
+            .set("clients/07-tendermint-0/clientState", vec![2]);
+        chain.produce_blocks(2).unwrap();
+
+        let key = "clients/07-tendermint-0/clientState";
+        let response = chain
+            .abci_query(
+                Some(IBC_STORE_KEY_PATH.to_string()),
+                key.as_bytes().to_vec(),
+                Some(1),
+                true,
+            )
+            .await
+            .unwrap();
+        let app_hash = &chain.light_block(4).unwrap().signed_header.header.app_hash;
+        assert!(matches!(
+            verify_kv_response(IBC_STORE, key, 1, response, app_hash.as_bytes()),
+            Err(KvProofError::Verification(_))
+        ));
+    }
+}
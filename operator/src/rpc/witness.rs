@@ -0,0 +1,300 @@
+//! A [`LightBlockProvider`] that cross-checks the headers of a primary provider against
+//! witnesses, to detect forks before proving.
+
+use std::{collections::HashSet, env, fmt, str::FromStr, sync::Mutex};
+
+use anyhow::Result;
+use async_trait::async_trait;
+use ibc_client_tendermint::types::Misbehaviour;
+use ibc_core_host_types::identifiers::ClientId;
+use ibc_proto::Protobuf;
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use tendermint::{block::signed_header::SignedHeader, node::Id as PeerId, Hash, Time};
+use tendermint_light_client_verifier::{
+    options::Options,
+    types::{LightBlock, ValidatorSet},
+    ProdVerifier, Verdict, Verifier,
+};
+use tendermint_rpc::endpoint::abci_query::AbciQuery;
+
+use super::{HttpProvider, LightBlockProvider};
+use crate::helpers::light_block::LightBlockExt;
+
+/// The client identifier put in the misbehaviour evidence. The field is deprecated in ICS-07 and
+/// the contract does not use it.
+const EVIDENCE_CLIENT_ID: &str = "07-tendermint-0";
+
+/// A [`LightBlockProvider`] that compares the hash of every header from the primary provider with
+/// the header at the same height from each witness, like the fork detector of the Tendermint
+/// light client.
+///
+/// A witness returning a different header fails the request with [`ForkDetected`], if the light
+/// block of the witness verifies from the trusted light block, see [`LightBlockProvider::trust`].
+/// Otherwise the witness is faulty, and it is dropped. A witness that cannot serve the height,
+/// for example because it is lagging, is skipped with a warning.
+pub struct WitnessedProvider<P, W = HttpProvider> {
+    primary: P,
+    witnesses: Vec<(String, W)>,
+    /// The light block the conflicting light blocks are verified from, and the options.
+    trusted: Mutex<Option<(LightBlock, Options)>>,
+    /// The indices of the faulty witnesses, which are not checked against anymore.
+    faulty: Mutex<HashSet<usize>>,
+}
+
+/// The error returned when a witness has a different header than the primary provider.
+#[derive(Debug, Clone)]
+pub struct ForkDetected {
+    /// The name of the witness.
+    pub witness: String,
+    /// The light block of the primary provider.
+    pub primary: Box<LightBlock>,
+    /// The conflicting light block of the witness.
+    pub conflicting: Box<LightBlock>,
+}
+
+/// Evidence of a fork, suitable for a misbehaviour submission.
+#[serde_as]
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MisbehaviourEvidence {
+    /// The name of the witness.
+    pub witness: String,
+    /// The height of the conflicting headers.
+    pub height: u64,
+    /// The trusted height the headers are verified from.
+    pub trusted_height: u64,
+    /// The light block of the primary provider.
+    pub primary: LightBlock,
+    /// The conflicting light block of the witness.
+    pub conflicting: LightBlock,
+    /// The protobuf encoded ICS-07 Tendermint `Misbehaviour` of the two headers.
+    #[serde_as(as = "serde_with::hex::Hex")]
+    pub misbehaviour: Vec<u8>,
+}
+
+impl ForkDetected {
+    /// Get the height of the conflicting headers.
+    #[must_use]
+    pub fn height(&self) -> u64 {
+        self.primary.height().value()
+    }
+
+    /// Create the misbehaviour evidence of the fork, with headers verified from
+    /// `trusted_light_block`.
+    ///
+    /// # Errors
+    /// Returns an error if the misbehaviour cannot be created.
+    pub fn misbehaviour_evidence(
+        &self,
+        trusted_light_block: &LightBlock,
+    ) -> Result<MisbehaviourEvidence> {
+        let misbehaviour = Misbehaviour::new(
+            ClientId::from_str(EVIDENCE_CLIENT_ID)?,
+            self.primary
+                .as_ref()
+                .clone()
+                .into_header(trusted_light_block),
+            self.conflicting
+                .as_ref()
+                .clone()
+                .into_header(trusted_light_block),
+        );
+
+        Ok(MisbehaviourEvidence {
+            witness: self.witness.clone(),
+            height: self.height(),
+            trusted_height: trusted_light_block.height().value(),
+            primary: self.primary.as_ref().clone(),
+            conflicting: self.conflicting.as_ref().clone(),
+            misbehaviour: misbehaviour.encode_vec(),
+        })
+    }
+}
+
+impl fmt::Display for ForkDetected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "fork detected at height {}: the primary header hash is {} but witness {} has {}",
+            self.height(),
+            self.primary.signed_header.header.hash(),
+            self.witness,
+            self.conflicting.signed_header.header.hash()
+        )
+    }
+}
+
+impl std::error::Error for ForkDetected {}
+
+impl<P: LightBlockProvider, W: LightBlockProvider> WitnessedProvider<P, W> {
+    /// Creates a new provider cross-checking `primary` against the named `witnesses`.
+    #[must_use]
+    pub fn new(primary: P, witnesses: Vec<(String, W)>) -> Self {
+        Self {
+            primary,
+            witnesses,
+            trusted: Mutex::new(None),
+            faulty: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Checks the header of `primary` at `height` against the witnesses.
+    async fn cross_check(&self, height: u32, primary: &SignedHeader) -> Result<()> {
+        let primary_hash = primary.header.hash();
+        for (i, (name, witness)) in self.witnesses.iter().enumerate() {
+            let is_faulty = self
+                .faulty
+                .lock()
+                .expect("faulty witnesses poisoned")
+                .contains(&i);
+            if is_faulty {
+                continue;
+            }
+            let witness_hash: Hash = match witness.signed_header(height).await {
+                Ok(signed_header) => signed_header.header.hash(),
+                Err(e) => {
+                    log::warn!("Witness {name} could not serve height {height}: {e:#}");
+                    continue;
+                }
+            };
+            if witness_hash == primary_hash {
+                continue;
+            }
+
+            let conflicting = match witness.light_block(height).await {
+                Ok(light_block) => light_block,
+                Err(e) => {
+                    log::warn!("Witness {name} could not serve height {height}: {e:#}");
+                    continue;
+                }
+            };
+            // Only a conflicting light block that a light client would accept is a fork.
+            if let Err(reason) = self.verify_conflicting(&conflicting) {
+                log::error!(
+                    "Witness {name} is faulty and is dropped: its header at height {height} \
+                     conflicts with the primary but {reason}"
+                );
+                self.faulty
+                    .lock()
+                    .expect("faulty witnesses poisoned")
+                    .insert(i);
+                continue;
+            }
+
+            let primary = self.primary.light_block(height).await?;
+            log::error!(
+                "Conflicting headers at height {height}:\nprimary: {}\nwitness {name}: {}",
+                serde_json::to_string(&primary.signed_header.header)?,
+                serde_json::to_string(&conflicting.signed_header.header)?
+            );
+            return Err(ForkDetected {
+                witness: name.clone(),
+                primary: Box::new(primary),
+                conflicting: Box::new(conflicting),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Verifies a conflicting light block of a witness from the trusted light block, with the
+    /// signatures and the trust threshold. Without a trusted light block below it, only its
+    /// validators and the signatures of its own validators are verified.
+    fn verify_conflicting(&self, conflicting: &LightBlock) -> Result<(), String> {
+        let verifier = ProdVerifier::default();
+        let untrusted = conflicting.as_untrusted_state();
+        let trusted = self.trusted.lock().expect("trusted light block poisoned");
+        let verdict = match trusted.as_ref() {
+            Some((trusted, options)) if trusted.height() < conflicting.height() => verifier
+                .verify_update_header(untrusted, trusted.as_trusted_state(), options, Time::now()),
+            _ => match verifier.verify_validator_sets(&untrusted) {
+                Verdict::Success => verifier.verify_commit(&untrusted),
+                verdict => verdict,
+            },
+        };
+        match verdict {
+            Verdict::Success => Ok(()),
+            Verdict::NotEnoughTrust(tally) => Err(format!("it does not verify: {tally}")),
+            Verdict::Invalid(detail) => Err(format!("it is invalid: {detail}")),
+        }
+    }
+}
+
+impl<P: LightBlockProvider> WitnessedProvider<P, HttpProvider> {
+    /// Creates a new provider cross-checking `primary` against the witnesses in the
+    /// `TENDERMINT_WITNESS_URLS` environment variable, a comma separated list of endpoints.
+    /// Without witnesses, the headers are not cross-checked. The witnesses use the default
+    /// transport options, as the headers and credentials of the primary endpoints are not meant
+    /// for them.
+    ///
+    /// # Panics
+    /// Panics if a witness URL is invalid.
+    #[must_use]
+    pub fn from_env(primary: P) -> Self {
+        let witnesses = env::var("TENDERMINT_WITNESS_URLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(|url| {
+                (
+                    url.to_string(),
+                    HttpProvider::new(url).expect("Failed to create witness HTTP client"),
+                )
+            })
+            .collect();
+        Self::new(primary, witnesses)
+    }
+}
+
+#[async_trait]
+impl<P: LightBlockProvider, W: LightBlockProvider> LightBlockProvider for WitnessedProvider<P, W> {
+    fn trust(&self, trusted_light_block: &LightBlock, options: &Options) {
+        *self.trusted.lock().expect("trusted light block poisoned") =
+            Some((trusted_light_block.clone(), *options));
+        self.primary.trust(trusted_light_block, options);
+    }
+
+    fn pin(&self) {
+        self.primary.pin();
+    }
+
+    fn unpin(&self) {
+        self.primary.unpin();
+    }
+
+    async fn peer_id(&self) -> Result<PeerId> {
+        self.primary.peer_id().await
+    }
+
+    async fn latest_height(&self) -> Result<u32> {
+        self.primary.latest_height().await
+    }
+
+    async fn signed_header(&self, height: u32) -> Result<SignedHeader> {
+        let signed_header = self.primary.signed_header(height).await?;
+        self.cross_check(height, &signed_header).await?;
+        Ok(signed_header)
+    }
+
+    async fn validators(&self, height: u32) -> Result<ValidatorSet> {
+        self.primary.validators(height).await
+    }
+
+    async fn abci_query(
+        &self,
+        path: Option<String>,
+        data: Vec<u8>,
+        height: Option<u32>,
+        prove: bool,
+    ) -> Result<AbciQuery> {
+        self.primary.abci_query(path, data, height, prove).await
+    }
+
+    async fn light_block(&self, height: u32) -> Result<LightBlock> {
+        let light_block = self.primary.light_block(height).await?;
+        self.cross_check(height, &light_block.signed_header).await?;
+        Ok(light_block)
+    }
+}
@@ -0,0 +1,250 @@
+//! Contains the runner for the `analyze` command.
+
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    time::Duration,
+};
+
+use alloy::providers::ProviderBuilder;
+use anyhow::Context;
+use reqwest::Url;
+use sp1_ics07_tendermint_solidity::sp1_ics07_tendermint;
+use tendermint::{
+    block::{signed_header::SignedHeader, CommitSig},
+    Time,
+};
+use tendermint_light_client_verifier::{
+    options::Options,
+    types::{LightBlock, TrustThreshold, ValidatorSet},
+};
+
+use crate::{
+    bisection,
+    cli::command::analyze::Args,
+    rpc::{CachingProvider, LightBlockProvider, TendermintRpcExt},
+};
+
+/// The voting power of a validator set that signed a commit.
+#[derive(Clone, Copy, Debug)]
+struct Tally {
+    signed: u64,
+    total: u64,
+}
+
+impl Tally {
+    /// Tallies the voting power of the `validators` that signed the commit of `signed_header`.
+    /// The signatures are not verified.
+    fn new(validators: &ValidatorSet, signed_header: &SignedHeader) -> Self {
+        let signers: HashSet<_> = signed_header
+            .commit
+            .signatures
+            .iter()
+            .filter_map(|signature| match signature {
+                CommitSig::BlockIdFlagCommit {
+                    validator_address, ..
+                } => Some(*validator_address),
+                _ => None,
+            })
+            .collect();
+        Self {
+            signed: validators
+                .validators()
+                .iter()
+                .filter(|validator| signers.contains(&validator.address))
+                .map(|validator| validator.power())
+                .sum(),
+            total: validators.total_voting_power().value(),
+        }
+    }
+
+    /// Whether the signed power is more than `threshold` of the total power.
+    fn exceeds(self, threshold: TrustThreshold) -> bool {
+        u128::from(self.signed) * u128::from(threshold.denominator())
+            > u128::from(self.total) * u128::from(threshold.numerator())
+    }
+
+    fn report(self, what: &str, threshold: TrustThreshold) {
+        #[allow(clippy::cast_precision_loss)]
+        let percent = 100.0 * self.signed as f64 / self.total.max(1) as f64;
+        println!(
+            "{what}: {}/{} ({percent:.2}%), more than {threshold} required: {}",
+            self.signed,
+            self.total,
+            if self.exceeds(threshold) {
+                "met"
+            } else {
+                "NOT MET"
+            }
+        );
+    }
+}
+
+/// Reports the validator set overlap between the trusted and target blocks, to diagnose why an
+/// update fails the trust threshold.
+#[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+pub async fn run(args: Args) -> anyhow::Result<()> {
+    match args.offline_options.provider()? {
+        Some(provider) => run_with_provider(args, &*provider).await,
+        None => run_with_provider(args, &CachingProvider::from_env()).await,
+    }
+}
+
+/// Reports the validator set overlap with the light blocks of `provider`. See [`run`].
+#[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+pub async fn run_with_provider(
+    args: Args,
+    provider: &(impl LightBlockProvider + ?Sized),
+) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        args.target_block > args.trusted_block,
+        "the target block must be after the trusted block"
+    );
+    let options = client_options(&args).await?;
+    println!(
+        "Trust level {}, trusting period {}s",
+        options.trust_threshold,
+        options.trusting_period.as_secs()
+    );
+    let trusted = provider.get_light_block(Some(args.trusted_block)).await?;
+    let target = provider.get_light_block(Some(args.target_block)).await?;
+
+    // Updates verify the target commit against the next validators of the trusted block.
+    let trusted_validators = &trusted.next_validators;
+    println!(
+        "Trusted block {}: {} next validators, total power {}",
+        args.trusted_block,
+        trusted_validators.validators().len(),
+        trusted_validators.total_voting_power().value()
+    );
+    println!(
+        "Target block {}: {} validators, total power {}",
+        args.target_block,
+        target.validators.validators().len(),
+        target.validators.total_voting_power().value()
+    );
+
+    Tally::new(trusted_validators, &target.signed_header).report(
+        "Trusted validators that signed the target commit",
+        options.trust_threshold,
+    );
+    Tally::new(&target.validators, &target.signed_header).report(
+        "Target validators that signed the target commit",
+        TrustThreshold::TWO_THIRDS,
+    );
+    report_churn(trusted_validators, &target.validators);
+
+    let expires_at = trusted
+        .signed_header
+        .header
+        .time
+        .checked_add(options.trusting_period)
+        .context("trusting period overflow")?;
+    if expires_at <= Time::now() {
+        println!(
+            "Trusted block {} expired at {expires_at}: no height verifies from it",
+            args.trusted_block
+        );
+        return Ok(());
+    }
+    println!(
+        "Trusted block {} expires at {expires_at}",
+        args.trusted_block
+    );
+
+    match furthest_verifiable_height(provider, &trusted, &options).await {
+        Ok((height, latest_height)) => println!(
+            "Largest height verifying from {}: {height} (latest {latest_height})",
+            args.trusted_block
+        ),
+        Err(e) => println!(
+            "Largest height verifying from {}: unknown ({e:#})",
+            args.trusted_block
+        ),
+    }
+
+    Ok(())
+}
+
+/// Gets the trust level and trusting period of the arguments, or else of the contract at
+/// `CONTRACT_ADDRESS`.
+async fn client_options(args: &Args) -> anyhow::Result<Options> {
+    let client_state = match env::var("CONTRACT_ADDRESS") {
+        Ok(contract_address) if args.trust_level.is_none() || args.trusting_period.is_none() => {
+            let rpc_url = env::var("RPC_URL").context("RPC_URL not set")?;
+            let eth_provider = ProviderBuilder::new().on_http(Url::parse(rpc_url.as_str())?);
+            let contract = sp1_ics07_tendermint::new(contract_address.parse()?, eth_provider);
+            Some(contract.getClientState().call().await?._0)
+        }
+        _ => None,
+    };
+
+    let trust_threshold = match (args.trust_level, &client_state) {
+        (Some(trust_level), _) => trust_level,
+        (None, Some(client_state)) => client_state.trustLevel.clone().into(),
+        (None, None) => anyhow::bail!("--trust-level is required without CONTRACT_ADDRESS"),
+    };
+    let trusting_period = match (args.trusting_period, &client_state) {
+        (Some(trusting_period), _) => trusting_period,
+        (None, Some(client_state)) => client_state.trustingPeriod,
+        (None, None) => anyhow::bail!("--trusting-period is required without CONTRACT_ADDRESS"),
+    };
+    Ok(Options {
+        trust_threshold,
+        trusting_period: Duration::from_secs(trusting_period.into()),
+        clock_drift: Duration::default(),
+    })
+}
+
+/// Reports the validators added to and removed from the trusted set, and the power changes.
+fn report_churn(trusted: &ValidatorSet, target: &ValidatorSet) {
+    let trusted_powers: HashMap<_, _> = trusted
+        .validators()
+        .iter()
+        .map(|validator| (validator.address, validator.power()))
+        .collect();
+    let target_powers: HashMap<_, _> = target
+        .validators()
+        .iter()
+        .map(|validator| (validator.address, validator.power()))
+        .collect();
+
+    let (mut added, mut added_power) = (0, 0);
+    let mut changed = 0;
+    for (address, power) in &target_powers {
+        match trusted_powers.get(address) {
+            None => {
+                added += 1;
+                added_power += power;
+            }
+            Some(trusted_power) if trusted_power != power => changed += 1,
+            Some(_) => {}
+        }
+    }
+    let (removed, removed_power) = trusted_powers
+        .iter()
+        .filter(|(address, _)| !target_powers.contains_key(address))
+        .fold((0, 0), |(count, sum), (_, power)| (count + 1, sum + power));
+
+    #[allow(clippy::cast_precision_loss)]
+    let removed_percent =
+        100.0 * removed_power as f64 / trusted.total_voting_power().value().max(1) as f64;
+    println!(
+        "Validator churn: {added} added (power {added_power}), {removed} removed (power \
+         {removed_power}, {removed_percent:.2}% of the trusted power), {changed} with a new power"
+    );
+}
+
+/// Finds the largest height up to the latest height that verifies from `trusted` in a single
+/// step with `options`, as an update of the client would. Returns the height and the latest
+/// height.
+async fn furthest_verifiable_height(
+    provider: &(impl LightBlockProvider + ?Sized),
+    trusted: &LightBlock,
+    options: &Options,
+) -> anyhow::Result<(u32, u32)> {
+    let latest_height = provider.latest_height().await?;
+    let furthest =
+        bisection::furthest_verifiable(provider, trusted, latest_height, options).await?;
+    Ok((u32::try_from(furthest.height().value())?, latest_height))
+}
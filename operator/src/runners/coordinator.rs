@@ -0,0 +1,20 @@
+//! Contains the runner for the `coordinator` command.
+
+use std::time::Duration;
+
+use crate::{cli::command::coordinator::Args, remote::coordinator::Coordinator};
+
+/// Runs the coordinator that distributes proof jobs to the registered workers.
+#[allow(clippy::missing_errors_doc)]
+pub async fn run(args: Args) -> anyhow::Result<()> {
+    let mut coordinator = Coordinator::new(
+        Duration::from_secs(args.job_timeout),
+        Duration::from_secs(args.result_ttl),
+    );
+    if let Some(token) = args.token {
+        coordinator = coordinator.with_token(token);
+    } else {
+        log::warn!("No token set, any client reaching the coordinator can submit and take jobs");
+    }
+    coordinator.serve(args.listen).await
+}
@@ -2,9 +2,9 @@
 
 use crate::{
     cli::command::{fixtures::MembershipCmd, OutputPath},
-    programs::MembershipProgram,
+    programs::{registry::ProgramSet, MembershipProgram},
     prover::SP1ICS07TendermintProver,
-    rpc::TendermintRpcExt,
+    rpc::{CachingProvider, LightBlockProvider, TendermintRpcExt, IBC_STORE},
     runners::genesis::SP1ICS07TendermintGenesis,
 };
 use alloy_sol_types::SolValue;
@@ -16,10 +16,8 @@ use sp1_ics07_tendermint_solidity::sp1_ics07_tendermint::{
     ClientState, ConsensusState as SolConsensusState, MembershipOutput, MembershipProof,
     SP1MembershipProof, SP1Proof,
 };
-use sp1_ics07_tendermint_utils::convert_tm_to_ics_merkle_proof;
 use sp1_sdk::HashableKey;
 use std::path::PathBuf;
-use tendermint_rpc::{Client, HttpClient};
 
 /// The fixture data to be used in [`MembershipProgram`] tests.
 #[serde_as]
@@ -35,24 +33,40 @@ pub struct SP1ICS07MembershipFixture {
     /// The encoded public values.
     #[serde_as(as = "serde_with::hex::Hex")]
     pub membership_proof: Vec<u8>,
+    /// The bincode encoded [`sp1_sdk::SP1ProofWithPublicValues`], to verify the fixture offline.
+    #[serde_as(as = "Option<serde_with::hex::Hex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof_with_public_values: Option<Vec<u8>>,
 }
 
 /// Writes the proof data for the given trusted and target blocks to the given fixture path.
 #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
 pub async fn run(args: MembershipCmd) -> anyhow::Result<()> {
+    match args.offline_options.provider()? {
+        Some(provider) => run_with_provider(args, &*provider).await,
+        None => run_with_provider(args, &CachingProvider::from_env()).await,
+    }
+}
+
+/// Writes the fixture with the light blocks and ABCI queries of `provider`. See [`run`].
+#[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+pub async fn run_with_provider(
+    args: MembershipCmd,
+    provider: &(impl LightBlockProvider + ?Sized),
+) -> anyhow::Result<()> {
     assert!(!args.key_paths.is_empty());
 
-    let tm_rpc_client = HttpClient::from_env();
     let verify_mem_prover = SP1ICS07TendermintProver::<MembershipProgram>::default();
 
-    let trusted_light_block = tm_rpc_client
-        .get_light_block(Some(args.trusted_block))
-        .await?;
+    let trusted_light_block = provider.get_light_block(Some(args.trusted_block)).await?;
 
-    let genesis = SP1ICS07TendermintGenesis::from_env(
+    let genesis = SP1ICS07TendermintGenesis::new(
+        provider,
         &trusted_light_block,
+        &args.trust_options.unbonding_period_source(),
         args.trust_options.trusting_period,
         args.trust_options.trust_level,
+        &ProgramSet::embedded(),
     )
     .await?;
 
@@ -64,30 +78,13 @@ pub async fn run(args: MembershipCmd) -> anyhow::Result<()> {
         .as_bytes()
         .to_vec();
 
-    let kv_proofs: Vec<(String, MerkleProof, Vec<u8>)> =
-        futures::future::try_join_all(args.key_paths.into_iter().map(|key_path| async {
-            let res = tm_rpc_client
-                .abci_query(
-                    Some("store/ibc/key".to_string()),
-                    key_path.as_bytes(),
-                    // Proof height should be the block before the target block.
-                    Some((args.trusted_block - 1).into()),
-                    true,
-                )
-                .await?;
-
-            assert_eq!(u32::try_from(res.height.value())? + 1, args.trusted_block);
-            assert_eq!(res.key.as_slice(), key_path.as_bytes());
-            let vm_proof = convert_tm_to_ics_merkle_proof(&res.proof.unwrap())?;
-            let value = res.value;
-            if value.is_empty() {
-                log::info!("Verifying non-membership");
-            }
-            assert!(!vm_proof.proofs.is_empty());
-
-            anyhow::Ok((key_path, vm_proof, value))
-        }))
-        .await?;
+    // Proof height should be the block before the trusted block.
+    let kv_proofs: Vec<(String, MerkleProof, Vec<u8>)> = futures::future::try_join_all(
+        args.key_paths
+            .iter()
+            .map(|key_path| provider.prove_kv(IBC_STORE, key_path, args.trusted_block - 1)),
+    )
+    .await?;
 
     // Generate a header update proof for the specified blocks.
     let proof_data = verify_mem_prover.generate_proof(&commitment_root_bytes, kv_proofs);
@@ -109,6 +106,7 @@ pub async fn run(args: MembershipCmd) -> anyhow::Result<()> {
         genesis,
         proof_height: trusted_client_state.latestHeight.abi_encode(),
         membership_proof: MembershipProof::from(sp1_membership_proof).abi_encode(),
+        proof_with_public_values: Some(bincode::serialize(&proof_data)?),
     };
 
     match args.output_path {
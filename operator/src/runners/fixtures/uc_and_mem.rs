@@ -3,9 +3,9 @@
 use crate::{
     cli::command::{fixtures::UpdateClientAndMembershipCmd, OutputPath},
     helpers::light_block::LightBlockExt,
-    programs::UpdateClientAndMembershipProgram,
+    programs::{registry::ProgramSet, UpdateClientAndMembershipProgram},
     prover::SP1ICS07TendermintProver,
-    rpc::TendermintRpcExt,
+    rpc::{CachingProvider, LightBlockProvider, TendermintRpcExt, IBC_STORE},
     runners::{
         fixtures::membership::SP1ICS07MembershipFixture, genesis::SP1ICS07TendermintGenesis,
     },
@@ -17,33 +17,41 @@ use sp1_ics07_tendermint_solidity::sp1_ics07_tendermint::{
     ClientState, ConsensusState as SolConsensusState, Env, MembershipProof,
     SP1MembershipAndUpdateClientProof, SP1Proof, UcAndMembershipOutput,
 };
-use sp1_ics07_tendermint_utils::convert_tm_to_ics_merkle_proof;
 use sp1_sdk::HashableKey;
 use std::path::PathBuf;
-use tendermint_rpc::{Client, HttpClient};
 
 /// Writes the proof data for the given trusted and target blocks to the given fixture path.
 #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
 pub async fn run(args: UpdateClientAndMembershipCmd) -> anyhow::Result<()> {
+    match args.offline_options.provider()? {
+        Some(provider) => run_with_provider(args, &*provider).await,
+        None => run_with_provider(args, &CachingProvider::from_env()).await,
+    }
+}
+
+/// Writes the fixture with the light blocks and ABCI queries of `provider`. See [`run`].
+#[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+pub async fn run_with_provider(
+    args: UpdateClientAndMembershipCmd,
+    provider: &(impl LightBlockProvider + ?Sized),
+) -> anyhow::Result<()> {
     assert!(
         args.trusted_block < args.target_block,
         "The target block must be greater than the trusted block"
     );
 
-    let tm_rpc_client = HttpClient::from_env();
     let uc_mem_prover = SP1ICS07TendermintProver::<UpdateClientAndMembershipProgram>::default();
 
-    let trusted_light_block = tm_rpc_client
-        .get_light_block(Some(args.trusted_block))
-        .await?;
-    let target_light_block = tm_rpc_client
-        .get_light_block(Some(args.target_block))
-        .await?;
+    let trusted_light_block = provider.get_light_block(Some(args.trusted_block)).await?;
+    let target_light_block = provider.get_light_block(Some(args.target_block)).await?;
 
-    let genesis = SP1ICS07TendermintGenesis::from_env(
+    let genesis = SP1ICS07TendermintGenesis::new(
+        provider,
         &trusted_light_block,
+        &args.trust_options.unbonding_period_source(),
         args.trust_options.trusting_period,
         args.trust_options.trust_level,
+        &ProgramSet::embedded(),
     )
     .await?;
     let trusted_client_state = ClientState::abi_decode(&genesis.trusted_client_state, false)?;
@@ -60,30 +68,13 @@ pub async fn run(args: UpdateClientAndMembershipCmd) -> anyhow::Result<()> {
             .as_secs(),
     };
 
-    let kv_proofs: Vec<(String, MerkleProof, Vec<u8>)> =
-        futures::future::try_join_all(args.key_paths.into_iter().map(|key_path| async {
-            let res = tm_rpc_client
-                .abci_query(
-                    Some("store/ibc/key".to_string()),
-                    key_path.as_bytes(),
-                    // Proof height should be the block before the target block.
-                    Some((args.target_block - 1).into()),
-                    true,
-                )
-                .await?;
-
-            assert_eq!(u32::try_from(res.height.value())? + 1, args.target_block);
-            assert_eq!(res.key.as_slice(), key_path.as_bytes());
-            let vm_proof = convert_tm_to_ics_merkle_proof(&res.proof.unwrap())?;
-            let value = res.value;
-            if value.is_empty() {
-                log::info!("Verifying non-membership");
-            }
-            assert!(!vm_proof.proofs.is_empty());
-
-            anyhow::Ok((key_path, vm_proof, value))
-        }))
-        .await?;
+    // Proof height should be the block before the target block.
+    let kv_proofs: Vec<(String, MerkleProof, Vec<u8>)> = futures::future::try_join_all(
+        args.key_paths
+            .iter()
+            .map(|key_path| provider.prove_kv(IBC_STORE, key_path, args.target_block - 1)),
+    )
+    .await?;
 
     let kv_len = kv_proofs.len();
     // Generate a header update proof for the specified blocks.
@@ -110,6 +101,7 @@ pub async fn run(args: UpdateClientAndMembershipCmd) -> anyhow::Result<()> {
         genesis,
         proof_height: output.updateClientOutput.newHeight.abi_encode(),
         membership_proof: MembershipProof::from(sp1_membership_proof).abi_encode(),
+        proof_with_public_values: Some(bincode::serialize(&proof_data)?),
     };
 
     match args.output_path {
@@ -3,9 +3,9 @@
 use crate::{
     cli::command::{fixtures::UpdateClientCmd, OutputPath},
     helpers::light_block::LightBlockExt,
-    programs::UpdateClientProgram,
+    programs::{registry::ProgramSet, UpdateClientProgram},
     prover::SP1ICS07TendermintProver,
-    rpc::TendermintRpcExt,
+    rpc::{CachingProvider, LightBlockProvider, TendermintRpcExt},
     runners::genesis::SP1ICS07TendermintGenesis,
 };
 use alloy_sol_types::SolValue;
@@ -16,48 +16,61 @@ use sp1_ics07_tendermint_solidity::sp1_ics07_tendermint::{
 };
 use sp1_sdk::HashableKey;
 use std::path::PathBuf;
-use tendermint_rpc::HttpClient;
 
 /// The fixture data to be used in [`UpdateClientProgram`] tests.
 #[serde_as]
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct SP1ICS07UpdateClientFixture {
+pub struct SP1ICS07UpdateClientFixture {
     /// The genesis data.
     #[serde(flatten)]
-    genesis: SP1ICS07TendermintGenesis,
+    pub genesis: SP1ICS07TendermintGenesis,
     /// The encoded target consensus state.
     #[serde_as(as = "serde_with::hex::Hex")]
-    target_consensus_state: Vec<u8>,
+    pub target_consensus_state: Vec<u8>,
     /// Target height.
-    target_height: u32,
+    pub target_height: u32,
     /// The encoded update client message.
     #[serde_as(as = "serde_with::hex::Hex")]
-    update_msg: Vec<u8>,
+    pub update_msg: Vec<u8>,
+    /// The bincode encoded [`sp1_sdk::SP1ProofWithPublicValues`], to verify the fixture offline.
+    #[serde_as(as = "Option<serde_with::hex::Hex>")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proof_with_public_values: Option<Vec<u8>>,
 }
 
 /// Writes the proof data for the given trusted and target blocks to the given fixture path.
 #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
 pub async fn run(args: UpdateClientCmd) -> anyhow::Result<()> {
+    match args.offline_options.provider()? {
+        Some(provider) => run_with_provider(args, &*provider).await,
+        None => run_with_provider(args, &CachingProvider::from_env()).await,
+    }
+}
+
+/// Writes the fixture with the light blocks and ABCI queries of `provider`. See [`run`].
+#[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+pub async fn run_with_provider(
+    args: UpdateClientCmd,
+    provider: &(impl LightBlockProvider + ?Sized),
+) -> anyhow::Result<()> {
     assert!(
         args.trusted_block < args.target_block,
         "The target block must be greater than the trusted block"
     );
 
-    let tm_rpc_client = HttpClient::from_env();
     let uc_prover = SP1ICS07TendermintProver::<UpdateClientProgram>::default();
 
-    let trusted_light_block = tm_rpc_client
-        .get_light_block(Some(args.trusted_block))
-        .await?;
-    let target_light_block = tm_rpc_client
-        .get_light_block(Some(args.target_block))
-        .await?;
+    let trusted_light_block = provider.get_light_block(Some(args.trusted_block)).await?;
+    let target_light_block = provider.get_light_block(Some(args.target_block)).await?;
 
-    let genesis = SP1ICS07TendermintGenesis::from_env(
+    let genesis = SP1ICS07TendermintGenesis::new(
+        provider,
         &trusted_light_block,
+        &args.trust_options.unbonding_period_source(),
         args.trust_options.trusting_period,
         args.trust_options.trust_level,
+        &ProgramSet::embedded(),
     )
     .await?;
 
@@ -95,6 +108,7 @@ pub async fn run(args: UpdateClientCmd) -> anyhow::Result<()> {
         target_consensus_state: output.newConsensusState.abi_encode(),
         target_height: args.target_block,
         update_msg: update_msg.abi_encode(),
+        proof_with_public_values: Some(bincode::serialize(&proof_data)?),
     };
 
     match args.output_path {
@@ -4,17 +4,19 @@ use crate::{
     cli::command::{genesis::Args, OutputPath},
     helpers::light_block::LightBlockExt,
     programs::{
-        MembershipProgram, SP1Program, UpdateClientAndMembershipProgram, UpdateClientProgram,
+        registry::{ProgramRegistry, ProgramSet, EMBEDDED_VERSION},
+        ProgramId,
     },
-    rpc::TendermintRpcExt,
+    rpc::{CachingProvider, LightBlockProvider, TendermintRpcExt},
+    unbonding::UnbondingPeriodSource,
 };
 use alloy_sol_types::SolValue;
+use anyhow::Context;
 use serde_with::serde_as;
 use sp1_ics07_tendermint_solidity::sp1_ics07_tendermint::ConsensusState as SolConsensusState;
-use sp1_sdk::{utils::setup_logger, HashableKey};
+use sp1_sdk::HashableKey;
 use std::path::PathBuf;
 use tendermint_light_client_verifier::types::{LightBlock, TrustThreshold};
-use tendermint_rpc::HttpClient;
 
 /// The genesis data for the SP1 ICS07 Tendermint contract.
 #[serde_as]
@@ -28,45 +30,30 @@ pub struct SP1ICS07TendermintGenesis {
     /// The encoded trusted consensus state.
     #[serde_as(as = "serde_with::hex::Hex")]
     pub trusted_consensus_state: Vec<u8>,
-    /// The encoded key for [`UpdateClientProgram`].
+    /// The encoded key for [`ProgramId::UpdateClient`].
     update_client_vkey: String,
-    /// The encoded key for [`MembershipProgram`].
+    /// The encoded key for [`ProgramId::Membership`].
     membership_vkey: String,
-    /// The encoded key for [`UpdateClientAndMembershipProgram`].
+    /// The encoded key for [`ProgramId::UpdateClientAndMembership`].
     uc_and_membership_vkey: String,
 }
 
 impl SP1ICS07TendermintGenesis {
-    /// Creates a new genesis instance by reading the environment variables
-    /// and making the necessary RPC calls.
+    /// Creates a new genesis instance for `trusted_light_block`, taking the unbonding period
+    /// from `unbonding_period_source`, queried with `provider` if needed. The verifying keys are
+    /// taken from `program_set`.
     #[allow(clippy::missing_errors_doc)]
-    pub async fn from_env(
+    pub async fn new(
+        provider: &(impl LightBlockProvider + ?Sized),
         trusted_light_block: &LightBlock,
+        unbonding_period_source: &UnbondingPeriodSource,
         trusting_period: Option<u32>,
         trust_level: TrustThreshold,
+        program_set: &ProgramSet,
     ) -> anyhow::Result<Self> {
-        setup_logger();
-        if dotenv::dotenv().is_err() {
-            log::warn!("No .env file found");
-        }
-
-        let tm_rpc_client = HttpClient::from_env();
-
-        let unbonding_period = tm_rpc_client
-            .sdk_staking_params()
-            .await?
-            .unbonding_time
-            .ok_or_else(|| anyhow::anyhow!("No unbonding time found"))?
-            .seconds
-            .try_into()?;
-
-        // Defaults to the recommended TrustingPeriod: 2/3 of the UnbondingPeriod
-        let trusting_period = trusting_period.unwrap_or(2 * (unbonding_period / 3));
-        if trusting_period > unbonding_period {
-            return Err(anyhow::anyhow!(
-                "Trusting period cannot be greater than unbonding period"
-            ));
-        }
+        let (unbonding_period, trusting_period) = unbonding_period_source
+            .periods(provider, trusting_period)
+            .await?;
 
         let trusted_client_state = trusted_light_block.to_sol_client_state(
             trust_level.try_into()?,
@@ -75,22 +62,101 @@ impl SP1ICS07TendermintGenesis {
         )?;
         let trusted_consensus_state = trusted_light_block.to_consensus_state();
 
+        let vkey = |program: ProgramId| {
+            program_set
+                .vkey(program)
+                .map(HashableKey::bytes32)
+                .ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "program {program} not found in version '{}'",
+                        program_set.version
+                    )
+                })
+        };
+
         Ok(Self {
             trusted_consensus_state: SolConsensusState::from(trusted_consensus_state).abi_encode(),
             trusted_client_state: trusted_client_state.abi_encode(),
-            update_client_vkey: UpdateClientProgram::get_vkey().bytes32(),
-            membership_vkey: MembershipProgram::get_vkey().bytes32(),
-            uc_and_membership_vkey: UpdateClientAndMembershipProgram::get_vkey().bytes32(),
+            update_client_vkey: vkey(ProgramId::UpdateClient)?,
+            membership_vkey: vkey(ProgramId::Membership)?,
+            uc_and_membership_vkey: vkey(ProgramId::UpdateClientAndMembership)?,
         })
     }
 }
 
+impl SP1ICS07TendermintGenesis {
+    /// Get the encoded verifying key of `program`.
+    #[must_use]
+    pub fn vkey(&self, program: ProgramId) -> &str {
+        match program {
+            ProgramId::UpdateClient => &self.update_client_vkey,
+            ProgramId::Membership => &self.membership_vkey,
+            ProgramId::UpdateClientAndMembership => &self.uc_and_membership_vkey,
+        }
+    }
+
+    /// Cross-check the verifying keys against the programs of `program_set`.
+    ///
+    /// # Errors
+    /// Returns an error listing the programs whose verifying key does not match.
+    pub fn check_vkeys(&self, program_set: &ProgramSet) -> anyhow::Result<()> {
+        let mut mismatches = Vec::new();
+        for program in ProgramId::ALL {
+            let vkey = program_set
+                .vkey(program)
+                .ok_or_else(|| anyhow::anyhow!("program {program} not found"))?
+                .bytes32();
+            if !self.vkey(program).eq_ignore_ascii_case(&vkey) {
+                mismatches.push(format!(
+                    "{program}: expected {vkey}, found {} in genesis",
+                    self.vkey(program)
+                ));
+            }
+        }
+        anyhow::ensure!(
+            mismatches.is_empty(),
+            "verifying keys of programs version '{}' do not match the genesis:\n{}",
+            program_set.version,
+            mismatches.join("\n")
+        );
+        Ok(())
+    }
+
+    /// Read a genesis file.
+    ///
+    /// # Errors
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn from_file(path: &std::path::Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read the genesis file {}", path.display()))?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+}
+
 /// Creates the `genesis.json` file for the `SP1ICS07Tendermint` contract.
 #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
 pub async fn run(args: Args) -> anyhow::Result<()> {
-    let tm_rpc_client = HttpClient::from_env();
+    match args.offline_options.provider()? {
+        Some(provider) => run_with_provider(args, &*provider).await,
+        None => run_with_provider(args, &CachingProvider::from_env()).await,
+    }
+}
 
-    let trusted_light_block = tm_rpc_client.get_light_block(args.trusted_block).await?;
+/// Creates the `genesis.json` file with the light blocks of `provider`. See [`run`].
+#[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+pub async fn run_with_provider(
+    args: Args,
+    provider: &(impl LightBlockProvider + ?Sized),
+) -> anyhow::Result<()> {
+    let registry = ProgramRegistry::load_or_embedded(args.program_options.programs.as_deref())?;
+    let program_set = registry.get_or_err(
+        args.program_options
+            .program_version
+            .as_deref()
+            .unwrap_or(EMBEDDED_VERSION),
+    )?;
+
+    let trusted_light_block = provider.get_light_block(args.trusted_block).await?;
     if args.trusted_block.is_none() {
         log::info!(
             "Latest block height: {}",
@@ -98,15 +164,39 @@ pub async fn run(args: Args) -> anyhow::Result<()> {
         );
     }
 
-    let genesis = SP1ICS07TendermintGenesis::from_env(
+    let genesis = SP1ICS07TendermintGenesis::new(
+        provider,
         &trusted_light_block,
+        &args.trust_options.unbonding_period_source(),
         args.trust_options.trusting_period,
         args.trust_options.trust_level,
+        program_set,
     )
     .await?;
 
     match args.output_path {
         OutputPath::File(path) => {
+            // Catch a change of programs before the deployed genesis is overwritten.
+            let path = std::path::Path::new(&path);
+            if path.exists() && !args.allow_vkey_change {
+                SP1ICS07TendermintGenesis::from_file(path)
+                    .with_context(|| {
+                        format!(
+                            "refusing to overwrite {}, which is not a valid genesis file, pass \
+                             --allow-vkey-change to overwrite it",
+                            path.display()
+                        )
+                    })?
+                    .check_vkeys(program_set)
+                    .map_err(|e| {
+                        e.context(format!(
+                            "refusing to overwrite {}, pass --allow-vkey-change to update the \
+                             programs",
+                            path.display()
+                        ))
+                    })?;
+            }
+
             // Save the proof data to the file path.
             std::fs::write(
                 PathBuf::from(path),
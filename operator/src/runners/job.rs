@@ -0,0 +1,249 @@
+//! Contains the runners for the `job` commands.
+
+use std::{env, path::PathBuf};
+
+use crate::{
+    cache::{ALLOWED_CLOCK_DRIFT, DEFAULT_MAX_PROOF_AGE},
+    cli::command::{
+        job::{MembershipCmd, ProveCmd, SubmitCmd, UpdateClientCmd},
+        OutputPath,
+    },
+    helpers,
+    job::{ProofJob, ProofJobResult},
+    programs::{
+        registry::{ProgramRegistry, EMBEDDED_VERSION},
+        ProgramId,
+    },
+    prover::SP1ICS07TendermintMultiProver,
+    rpc::{CachingProvider, LightBlockProvider, TendermintRpcExt, IBC_STORE},
+};
+use alloy::providers::ProviderBuilder;
+use alloy_sol_types::SolValue;
+use ibc_core_commitment_types::merkle::MerkleProof;
+use log::info;
+use reqwest::Url;
+use sp1_ics07_tendermint_solidity::sp1_ics07_tendermint::{self, MsgUpdateClient, SP1Proof};
+use sp1_sdk::HashableKey;
+
+/// Creates an update client job from the contract's latest height to the target block.
+#[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+pub async fn run_update_client(args: UpdateClientCmd) -> anyhow::Result<()> {
+    match args.offline_options.provider()? {
+        Some(provider) => run_update_client_with_provider(args, &*provider).await,
+        None => run_update_client_with_provider(args, &CachingProvider::from_env()).await,
+    }
+}
+
+/// Creates an update client job with the light blocks of `provider`. See [`run_update_client`].
+#[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+pub async fn run_update_client_with_provider(
+    args: UpdateClientCmd,
+    provider: &(impl LightBlockProvider + ?Sized),
+) -> anyhow::Result<()> {
+    let rpc_url = env::var("RPC_URL").expect("RPC_URL not set");
+    let contract_address = env::var("CONTRACT_ADDRESS").expect("CONTRACT_ADDRESS not set");
+
+    let eth_provider = ProviderBuilder::new().on_http(Url::parse(rpc_url.as_str())?);
+    let contract = sp1_ics07_tendermint::new(contract_address.parse()?, eth_provider);
+
+    let registry = ProgramRegistry::load_or_embedded(args.program_options.programs.as_deref())?;
+    let contract_vkey = contract
+        .UPDATE_CLIENT_PROGRAM_VKEY()
+        .call()
+        .await?
+        ._0
+        .to_string();
+    let program_set = registry.select(
+        args.program_options.program_version.as_deref(),
+        ProgramId::UpdateClient,
+        &contract_vkey,
+    )?;
+    let vkey = program_set
+        .vkey(ProgramId::UpdateClient)
+        .ok_or_else(|| anyhow::anyhow!("update client program not found"))?
+        .bytes32();
+
+    let contract_client_state = contract.getClientState().call().await?._0;
+    let trusted_light_block = provider
+        .get_light_block(Some(contract_client_state.latestHeight.revisionHeight))
+        .await?;
+    let target_light_block = provider.get_light_block(args.target_block).await?;
+
+    let job = ProofJob::update_client(
+        vkey,
+        &trusted_light_block,
+        target_light_block,
+        contract_client_state.trustLevel,
+        contract_client_state.trustingPeriod,
+    )?;
+    info!(
+        "Created update client job from block {} to block {:?}",
+        job.metadata.trusted_height, job.metadata.target_height
+    );
+
+    write_output(args.output_path, &job)
+}
+
+/// Creates a verify (non)membership job for the given key paths at the trusted block.
+#[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+pub async fn run_membership(args: MembershipCmd) -> anyhow::Result<()> {
+    match args.offline_options.provider()? {
+        Some(provider) => run_membership_with_provider(args, &*provider).await,
+        None => run_membership_with_provider(args, &CachingProvider::from_env()).await,
+    }
+}
+
+/// Creates a verify (non)membership job with the light blocks and ABCI queries of `provider`.
+/// See [`run_membership`].
+#[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+pub async fn run_membership_with_provider(
+    args: MembershipCmd,
+    provider: &(impl LightBlockProvider + ?Sized),
+) -> anyhow::Result<()> {
+    anyhow::ensure!(!args.key_paths.is_empty(), "no key paths given");
+    // The key-value pairs are proven at the height before the trusted block.
+    let proof_height = args
+        .trusted_block
+        .checked_sub(1)
+        .ok_or_else(|| anyhow::anyhow!("the trusted block must be at least 1"))?;
+
+    let registry = ProgramRegistry::load_or_embedded(args.program_options.programs.as_deref())?;
+    let program_set = registry.get_or_err(
+        args.program_options
+            .program_version
+            .as_deref()
+            .unwrap_or(EMBEDDED_VERSION),
+    )?;
+    let vkey = program_set
+        .vkey(ProgramId::Membership)
+        .ok_or_else(|| anyhow::anyhow!("membership program not found"))?
+        .bytes32();
+
+    let trusted_light_block = provider.get_light_block(Some(args.trusted_block)).await?;
+
+    let kv_proofs: Vec<(String, MerkleProof, Vec<u8>)> = futures::future::try_join_all(
+        args.key_paths
+            .iter()
+            .map(|key_path| provider.prove_kv(IBC_STORE, key_path, proof_height)),
+    )
+    .await?;
+
+    let job = ProofJob::membership(vkey, &trusted_light_block, kv_proofs)?;
+    info!(
+        "Created membership job for {} key paths at block {}",
+        job.metadata.key_paths.len(),
+        job.metadata.trusted_height
+    );
+
+    write_output(args.output_path, &job)
+}
+
+/// Proves a job with the program version matching the job's verifying key, locally or with the
+/// workers of a coordinator. Remote proofs are verified against the same program.
+#[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+pub async fn run_prove(args: ProveCmd) -> anyhow::Result<()> {
+    let job = ProofJob::load(&args.job)?;
+    // Leave time to prove and submit before the contract rejects the proof as too old.
+    job.ensure_fresh(DEFAULT_MAX_PROOF_AGE)?;
+
+    let registry = ProgramRegistry::load_or_embedded(args.program_options.programs.as_deref())?;
+    let program_set = registry.select(
+        args.program_options.program_version.as_deref(),
+        job.program,
+        &job.vkey,
+    )?;
+    info!(
+        "Proving {} job with programs version '{}'",
+        job.program, program_set.version
+    );
+
+    let mut prover = SP1ICS07TendermintMultiProver::empty();
+    if let Some(cache) = args.cache_options.open()? {
+        info!("Using the proof cache at {}", cache.dir().display());
+        prover = prover.with_cache(cache);
+    }
+    prover.register_elf(
+        job.program,
+        program_set
+            .elf(job.program)
+            .ok_or_else(|| anyhow::anyhow!("program {} not found", job.program))?,
+    );
+    let vkey = prover
+        .vkey(job.program)
+        .expect("program is registered")
+        .bytes32();
+    // The contract the job was created for only accepts proofs of its own verifying key.
+    anyhow::ensure!(
+        vkey.eq_ignore_ascii_case(&job.vkey),
+        "programs version '{}' has vkey {vkey}, but the job was created for vkey {}",
+        program_set.version,
+        job.vkey
+    );
+
+    // Prove with the workers of the coordinator if given, checking their proof locally.
+    let proof = if let Some(coordinator) = args.remote_options.client()? {
+        coordinator
+            .prove(&job, &prover, args.remote_options.poll_interval())
+            .await?
+    } else {
+        tokio::task::block_in_place(|| prover.prove(job.program, job.sp1_stdin()))?
+    };
+    let result = ProofJobResult::new(job, &proof)?;
+
+    write_output(args.output_path, &result)
+}
+
+/// Submits a proven update client job to the contract.
+#[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+pub async fn run_submit(args: SubmitCmd) -> anyhow::Result<()> {
+    let result = ProofJobResult::load(&args.result)?;
+    anyhow::ensure!(
+        result.job.program == ProgramId::UpdateClient,
+        "only update client jobs can be submitted, found a {} job",
+        result.job.program
+    );
+    result.job.ensure_fresh(ALLOWED_CLOCK_DRIFT)?;
+    let proof = result.sp1_proof()?;
+
+    let rpc_url = env::var("RPC_URL").expect("RPC_URL not set");
+    let contract_address = env::var("CONTRACT_ADDRESS").expect("CONTRACT_ADDRESS not set");
+
+    let wallet = helpers::eth::wallet_from_env();
+    let provider = ProviderBuilder::new()
+        .with_recommended_fillers()
+        .wallet(wallet)
+        .on_http(Url::parse(rpc_url.as_str())?);
+    let contract = sp1_ics07_tendermint::new(contract_address.parse()?, provider);
+
+    let update_msg = MsgUpdateClient {
+        sp1Proof: SP1Proof::new(
+            &result.job.vkey,
+            proof.bytes(),
+            proof.public_values.to_vec(),
+        ),
+    };
+
+    contract
+        .updateClient(update_msg.abi_encode().into())
+        .send()
+        .await?
+        .watch()
+        .await?;
+
+    info!(
+        "Updated the ICS-07 Tendermint light client at address {} from block {} to block {:?}.",
+        contract_address, result.job.metadata.trusted_height, result.job.metadata.target_height
+    );
+
+    Ok(())
+}
+
+/// Writes `value` as pretty JSON to the output path.
+fn write_output(output_path: OutputPath, value: &impl serde::Serialize) -> anyhow::Result<()> {
+    let json = serde_json::to_string_pretty(value)?;
+    match output_path {
+        OutputPath::File(path) => std::fs::write(PathBuf::from(path), json)?,
+        OutputPath::Stdout => println!("{json}"),
+    }
+    Ok(())
+}
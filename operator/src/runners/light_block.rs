@@ -0,0 +1,67 @@
+//! Contains the runner for the `light-block` command.
+
+use crate::{
+    cli::command::{light_block::Args, OutputPath},
+    rpc::{
+        CachingProvider, LightBlockFile, LightBlockProvider, SavedAbciQuery, TendermintRpcExt,
+        IBC_STORE,
+    },
+};
+
+/// Saves the light block at the given height, with the staking parameters and the membership
+/// proofs of the given key paths, to a light block file.
+#[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+pub async fn run(args: Args) -> anyhow::Result<()> {
+    run_with_provider(args, &CachingProvider::from_env()).await
+}
+
+/// Saves the light block file with the light blocks and ABCI queries of `provider`. See [`run`].
+#[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+pub async fn run_with_provider(
+    args: Args,
+    provider: &(impl LightBlockProvider + ?Sized),
+) -> anyhow::Result<()> {
+    let light_block = provider.get_light_block(args.height).await?;
+    let height = u32::try_from(light_block.height().value())?;
+    log::info!("Saving the light block at height {height}");
+
+    let staking_params = match provider.sdk_staking_params().await {
+        Ok(params) => Some(params),
+        Err(e) => {
+            log::warn!("Saving the light block without staking params: {e:#}");
+            None
+        }
+    };
+
+    let abci_queries =
+        futures::future::try_join_all(args.key_paths.into_iter().map(|key_path| async move {
+            // The same query as `TendermintRpcExt::prove_kv`, to be served offline.
+            let path = Some(format!("store/{IBC_STORE}/key"));
+            let data = key_path.into_bytes();
+            // Proof height should be the block before the light block.
+            let query_height = Some(height - 1);
+            let response = provider
+                .abci_query(path.clone(), data.clone(), query_height, true)
+                .await?;
+            anyhow::Ok(SavedAbciQuery {
+                path,
+                data,
+                height: query_height,
+                response,
+            })
+        }))
+        .await?;
+
+    let light_block_file = LightBlockFile::new(light_block, staking_params, abci_queries)?;
+
+    match args.output_path {
+        OutputPath::File(path) => {
+            std::fs::write(path, serde_json::to_string_pretty(&light_block_file)?)?;
+        }
+        OutputPath::Stdout => {
+            println!("{}", serde_json::to_string_pretty(&light_block_file)?);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,12 @@
+//! Contains the runner for the `mock-rpc` executable.
+
+use crate::{cli::command::mock_rpc::Args, rpc::MockRpcServer};
+
+/// Serves the light block files or the synthetic chain of the arguments over RPC.
+#[allow(clippy::missing_errors_doc)]
+pub async fn run(args: Args) -> anyhow::Result<()> {
+    let provider = args.offline_options.provider()?.ok_or_else(|| {
+        anyhow::anyhow!("either --light-block-file or --synthetic-chain is required")
+    })?;
+    MockRpcServer::new(provider).serve(args.listen).await
+}
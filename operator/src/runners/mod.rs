@@ -1,5 +1,13 @@
 //! Contains the runners for the different types of commands.
 
+pub mod analyze;
+pub mod coordinator;
 pub mod fixtures;
 pub mod genesis;
+pub mod job;
+pub mod light_block;
+pub mod mock_rpc;
 pub mod operator;
+pub mod verify;
+pub mod vkeys;
+pub mod worker;
@@ -1,26 +1,44 @@
 //! Contains the runner for the `operator run` command.
 
-use std::env;
+use std::{
+    collections::HashMap,
+    env, fs,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use crate::{
+    bisection,
+    cache::ProofCache,
     cli::command::operator::Args,
-    helpers::{self, light_block::LightBlockExt},
-    programs::UpdateClientProgram,
-    prover::SP1ICS07TendermintProver,
-    rpc::TendermintRpcExt,
+    config::{ClientConfig, OperatorConfig},
+    helpers,
+    job::ProofJob,
+    programs::{
+        registry::{ProgramRegistry, ProgramSet},
+        ProgramId,
+    },
+    prover::SP1ICS07TendermintMultiProver,
+    rpc::{CachingProvider, ForkDetected, LightBlockProvider, NewBlocks, TendermintRpcExt},
+    trigger::{ClientStatus, Decision, UpdateRequests},
+    watchdog::{self, ExpiryError, ExpiryLevel, ExpiryMetrics, EXIT_CODE_EXPIRED},
 };
-use alloy::providers::ProviderBuilder;
+use alloy::{network::EthereumWallet, providers::ProviderBuilder};
 use alloy_sol_types::SolValue;
-use log::{debug, info};
+use log::{debug, error, info, warn};
 use reqwest::Url;
-use sp1_ics07_tendermint_solidity::sp1_ics07_tendermint::{self, Env, MsgUpdateClient, SP1Proof};
+use sp1_ics07_tendermint_solidity::sp1_ics07_tendermint::{self, MsgUpdateClient, SP1Proof};
 use sp1_sdk::{utils::setup_logger, HashableKey};
-use tendermint_rpc::HttpClient;
+use tendermint::Time;
+use tendermint_light_client_verifier::options::Options;
+use tokio::sync::{Mutex, Notify};
 
 /// An implementation of a Tendermint Light Client operator that will poll an onchain Tendermint
 /// light client and generate a proof of the transition from the latest block in the contract to the
 /// latest block on the chain. Then, submits the proof to the contract and updates the contract with
 /// the latest block hash and height.
+///
+/// With `--config`, drives every client of the configuration file concurrently instead.
 #[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
 pub async fn run(args: Args) -> anyhow::Result<()> {
     setup_logger();
@@ -28,20 +46,336 @@ pub async fn run(args: Args) -> anyhow::Result<()> {
         log::warn!("No .env file found");
     }
 
-    let rpc_url = env::var("RPC_URL").expect("RPC_URL not set");
+    // Check the thresholds before starting the clients, which would be restarted forever.
+    args.watchdog_options.thresholds()?;
+    if let Some(path) = &args.config {
+        let config = OperatorConfig::load(path)?;
+        let requests = serve_update_requests(&args, config.clients.iter().map(|c| c.name.clone()));
+        let metrics = serve_metrics(&args);
+        return run_clients(&args, config, &requests, &metrics).await;
+    }
+    run_with_provider(args, &CachingProvider::from_env()).await
+}
+
+/// Runs the operator with the light blocks of `provider`, for the contract in the environment
+/// variables. See [`run`].
+#[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+pub async fn run_with_provider(
+    args: Args,
+    provider: &(impl LightBlockProvider + ?Sized),
+) -> anyhow::Result<()> {
     let contract_address = env::var("CONTRACT_ADDRESS").expect("CONTRACT_ADDRESS not set");
+    let requests = serve_update_requests(&args, [contract_address.clone()]);
+    let client = Client {
+        update_requests: requests.client(&contract_address),
+        name: contract_address.clone(),
+        eth_rpc_url: env::var("RPC_URL").expect("RPC_URL not set"),
+        contract_address,
+        wallet: helpers::eth::wallet_from_env(),
+        metrics: serve_metrics(&args),
+        provers: Arc::default(),
+    };
+    run_client(args, &client, provider)
+        .await
+        .map_err(|e| client.metrics.escalate(&client.name, e))
+}
+
+/// A light client contract driven by the operator, and the signer of its updates.
+#[derive(Clone)]
+pub struct Client {
+    /// The name of the client in the logs.
+    pub name: String,
+    /// The URL of the EVM RPC node.
+    pub eth_rpc_url: String,
+    /// The address of the `SP1ICS07Tendermint` contract.
+    pub contract_address: String,
+    /// The wallet signing the updates.
+    pub wallet: EthereumWallet,
+    /// The notifications of the update requests of the client.
+    pub update_requests: Arc<Notify>,
+    /// The expiry metrics the client records its expiry to.
+    pub metrics: Arc<ExpiryMetrics>,
+    /// The provers shared with the other clients.
+    pub provers: Arc<Provers>,
+}
+
+/// The provers of the update client program, set up once per verifying key and shared by the
+/// clients, and the restarts of a client, that use it.
+#[derive(Default)]
+pub struct Provers {
+    provers: Mutex<HashMap<String, Arc<SP1ICS07TendermintMultiProver>>>,
+}
+
+impl Provers {
+    /// Gets the prover of the update client program of `program_set`, setting it up with `cache`
+    /// if it is the first one.
+    async fn get_or_setup(
+        &self,
+        program_set: &ProgramSet,
+        cache: Option<ProofCache>,
+    ) -> anyhow::Result<Arc<SP1ICS07TendermintMultiProver>> {
+        let vkey = program_set
+            .vkey(ProgramId::UpdateClient)
+            .ok_or_else(|| anyhow::anyhow!("update client program not found"))?
+            .bytes32();
+        let elf = program_set
+            .elf(ProgramId::UpdateClient)
+            .ok_or_else(|| anyhow::anyhow!("update client program not found"))?;
+
+        // Hold the lock while setting up, so that the clients starting together set up once.
+        let mut provers = self.provers.lock().await;
+        if let Some(prover) = provers.get(&vkey) {
+            return Ok(Arc::clone(prover));
+        }
+        let prover = tokio::task::block_in_place(|| {
+            let mut prover = SP1ICS07TendermintMultiProver::empty();
+            if let Some(cache) = cache {
+                info!("Using the proof cache at {}", cache.dir().display());
+                prover = prover.with_cache(cache);
+            }
+            prover.register_elf(ProgramId::UpdateClient, elf);
+            Arc::new(prover)
+        });
+        provers.insert(vkey, Arc::clone(&prover));
+        drop(provers);
+        Ok(prover)
+    }
+}
+
+/// Creates the update requests of the clients `names`, and serves them if `--request-listen` is
+/// set.
+fn serve_update_requests(
+    args: &Args,
+    names: impl IntoIterator<Item = String>,
+) -> Arc<UpdateRequests> {
+    let requests = Arc::new(UpdateRequests::new(names));
+    if let Some(addr) = args.request_listen {
+        let server = Arc::clone(&requests);
+        tokio::spawn(async move {
+            if let Err(e) = server.serve(addr).await {
+                error!("Update request server stopped: {e:#}");
+            }
+        });
+    }
+    requests
+}
+
+/// Creates the expiry metrics of the clients, and serves them if `--metrics-listen` is set.
+fn serve_metrics(args: &Args) -> Arc<ExpiryMetrics> {
+    let metrics = Arc::new(ExpiryMetrics::default());
+    if let Some(addr) = args.metrics_listen {
+        let server = Arc::clone(&metrics);
+        tokio::spawn(async move {
+            if let Err(e) = server.serve(addr).await {
+                error!("Metrics server stopped: {e:#}");
+            }
+        });
+    }
+    metrics
+}
+
+/// Runs every client of `config` concurrently, each in its own task, with `args` overridden by
+/// the policy of the client. A failing client is restarted after its poll interval without
+/// affecting the others, unless `--only-once` is set, a fork is detected or the client expired.
+///
+/// Returns the error of the stopped client with the highest exit code, if any.
+async fn run_clients(
+    args: &Args,
+    config: OperatorConfig,
+    requests: &UpdateRequests,
+    metrics: &Arc<ExpiryMetrics>,
+) -> anyhow::Result<()> {
+    let provers = Arc::new(Provers::default());
+    let mut tasks = Vec::with_capacity(config.clients.len());
+    for client_config in config.clients {
+        let args = client_args(args, &client_config)?;
+        let provider = client_config.provider()?;
+        let client = Client {
+            wallet: helpers::eth::wallet_from_env_var(&client_config.private_key_env),
+            update_requests: requests.client(&client_config.name),
+            metrics: Arc::clone(metrics),
+            provers: Arc::clone(&provers),
+            name: client_config.name,
+            eth_rpc_url: client_config.eth_rpc_url,
+            contract_address: client_config.contract_address,
+        };
+        tasks.push((
+            client.name.clone(),
+            tokio::spawn(run_isolated(args, client, provider)),
+        ));
+    }
+    info!("Driving {} clients", tasks.len());
+
+    let mut failed = 0;
+    let mut exit_error: Option<anyhow::Error> = None;
+    for (name, task) in tasks {
+        let result = match task.await {
+            Ok(result) => result,
+            Err(e) => Err(anyhow::anyhow!("task failed: {e}")),
+        };
+        if let Err(e) = result {
+            error!("[{name}] Stopped: {e:#}");
+            failed += 1;
+            if watchdog::exit_code(&e) > exit_error.as_ref().and_then(watchdog::exit_code) {
+                exit_error = Some(e);
+            }
+        }
+    }
+    if let Some(e) = exit_error {
+        return Err(e.context(format!("{failed} clients stopped with an error")));
+    }
+    anyhow::ensure!(failed == 0, "{failed} clients stopped with an error");
+    Ok(())
+}
+
+/// Runs a client of the configuration file, restarting it when it fails. See [`run_clients`].
+async fn run_isolated(
+    args: Args,
+    client: Client,
+    provider: impl LightBlockProvider,
+) -> anyhow::Result<()> {
+    loop {
+        let result = run_client(args.clone(), &client, &provider).await;
+        match result.map_err(|e| client.metrics.escalate(&client.name, e)) {
+            Ok(()) => return Ok(()),
+            Err(e)
+                if args.only_once
+                    || e.downcast_ref::<ForkDetected>().is_some()
+                    || watchdog::exit_code(&e) == Some(EXIT_CODE_EXPIRED) =>
+            {
+                return Err(e);
+            }
+            Err(e) => {
+                error!(
+                    "[{}] {e:#}, restarting in {} seconds",
+                    client.name, args.poll_interval
+                );
+                tokio::time::sleep(Duration::from_secs(args.poll_interval)).await;
+            }
+        }
+    }
+}
 
-    // Instantiate a Tendermint prover based on the environment variable.
-    let wallet = helpers::eth::wallet_from_env();
-    let provider = ProviderBuilder::new()
+/// Overrides `args` with the websocket endpoint, the compatibility mode and the policy of a
+/// client.
+fn client_args(args: &Args, config: &ClientConfig) -> anyhow::Result<Args> {
+    let mut args = args.clone();
+    args.websocket_url.clone_from(&config.websocket_url);
+    args.rpc_compat_mode = config.transport.compat_mode()?.or(args.rpc_compat_mode);
+    let policy = &config.policy;
+    args.poll_interval = policy.poll_interval.unwrap_or(args.poll_interval);
+    args.max_updates = policy.max_updates.unwrap_or(args.max_updates);
+    args.furthest_only = policy.furthest_only.unwrap_or(args.furthest_only);
+
+    let triggers = &mut args.trigger_options;
+    triggers.max_height_lag = policy.max_height_lag.or(triggers.max_height_lag);
+    triggers.trusting_period_fraction = policy
+        .trusting_period_fraction
+        .or(triggers.trusting_period_fraction);
+    triggers.on_request = policy.on_request.unwrap_or(triggers.on_request);
+    triggers.min_update_interval = policy.min_update_interval.or(triggers.min_update_interval);
+    triggers.max_height_jump = policy.max_height_jump.or(triggers.max_height_jump);
+    Ok(args)
+}
+
+/// Handles a failure to read the chain, which is retried on the next block unless a fork was
+/// detected, or the client is about to expire and cannot wait for the node to recover. These
+/// failures are returned, escalated to an [`ExpiryError`] if the client is about to expire.
+fn chain_read_failed(client: &Client, e: anyhow::Error) -> anyhow::Result<()> {
+    let critical = client
+        .metrics
+        .expiry(&client.name)
+        .is_some_and(|(_, expiry)| expiry.level >= ExpiryLevel::Critical);
+    if critical || e.downcast_ref::<ForkDetected>().is_some() {
+        return Err(client.metrics.escalate(&client.name, e));
+    }
+    warn!(
+        "[{}] Failed to read the chain, retrying: {e:#}",
+        client.name
+    );
+    Ok(())
+}
+
+/// Replaces the characters of a client name that are not safe in a file name.
+fn file_name_safe(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Waits for a new block or an update request. Returns whether an update was requested.
+async fn wait_for_update(new_blocks: &mut NewBlocks, update_requests: &Notify) -> bool {
+    tokio::select! {
+        () = new_blocks.next() => false,
+        () = update_requests.notified() => true,
+    }
+}
+
+/// Runs the operator for `client` with the light blocks of `provider`. See [`run`].
+#[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+pub async fn run_client(
+    args: Args,
+    client: &Client,
+    provider: &(impl LightBlockProvider + ?Sized),
+) -> anyhow::Result<()> {
+    let contract_address = &client.contract_address;
+    let eth_provider = ProviderBuilder::new()
         .with_recommended_fillers()
-        .wallet(wallet)
-        .on_http(Url::parse(rpc_url.as_str())?);
+        .wallet(client.wallet.clone())
+        .on_http(Url::parse(client.eth_rpc_url.as_str())?);
+
+    let contract = sp1_ics07_tendermint::new(contract_address.parse()?, eth_provider);
 
-    let contract = sp1_ics07_tendermint::new(contract_address.parse()?, provider);
-    let tendermint_rpc_client = HttpClient::from_env();
-    let prover = SP1ICS07TendermintProver::<UpdateClientProgram>::default();
+    // Select the programs matching the contract, unless a version is given explicitly.
+    let registry = ProgramRegistry::load_or_embedded(args.program_options.programs.as_deref())?;
+    let contract_vkey = contract
+        .UPDATE_CLIENT_PROGRAM_VKEY()
+        .call()
+        .await?
+        ._0
+        .to_string();
+    let program_set = registry.select(
+        args.program_options.program_version.as_deref(),
+        ProgramId::UpdateClient,
+        &contract_vkey,
+    )?;
+    info!(
+        "[{}] Using programs version '{}'",
+        client.name, program_set.version
+    );
 
+    let update_client_vkey = program_set
+        .vkey(ProgramId::UpdateClient)
+        .ok_or_else(|| anyhow::anyhow!("update client program not found"))?
+        .bytes32();
+
+    // Prove with the workers of the coordinator if given, otherwise locally. The prover also
+    // verifies the proofs of the workers.
+    let coordinator = args.remote_options.client()?;
+    let cache = if coordinator.is_none() {
+        args.cache_options.open()?
+    } else {
+        None
+    };
+    let prover = client.provers.get_or_setup(program_set, cache).await?;
+
+    // Update on new blocks if subscribed, otherwise poll.
+    let poll_interval = Duration::from_secs(args.poll_interval);
+    let mut new_blocks = match args.websocket_url.as_deref() {
+        Some(url) => NewBlocks::websocket(url, poll_interval, args.rpc_compat_mode)?,
+        None => NewBlocks::polling(poll_interval),
+    };
+
+    let policy = args.trigger_options.policy();
+    let thresholds = args.watchdog_options.thresholds()?;
+    let mut requested = false;
+    let mut last_update: Option<Instant> = None;
     loop {
         let contract_client_state = contract.getClientState().call().await?._0;
 
@@ -52,59 +386,229 @@ pub async fn run(args: Args) -> anyhow::Result<()> {
             "No trusted height found on the contract. Something is wrong with the contract."
         );
 
-        let trusted_light_block = tendermint_rpc_client
-            .get_light_block(Some(trusted_block_height))
-            .await?;
-
-        // Get trusted consensus state from the trusted light block.
-        let trusted_consensus_state = trusted_light_block.to_consensus_state().into();
+        let trusting_period = Duration::from_secs(contract_client_state.trustingPeriod.into());
+        let options = Options {
+            trust_threshold: contract_client_state.trustLevel.clone().into(),
+            trusting_period,
+            clock_drift: Duration::default(),
+        };
 
-        let target_light_block = tendermint_rpc_client.get_light_block(None).await?;
-        let target_height = target_light_block.height().value();
+        // Read the chain from a single endpoint, so that the latest height and the light blocks
+        // agree, and retry on the next block if it fails. Only the trusted header is needed for
+        // the expiry and the decision.
+        provider.pin();
+        let chain = async {
+            let trusted_header = provider.signed_header(trusted_block_height).await?;
+            let latest_height = provider.latest_height().await?;
+            anyhow::Ok((trusted_header, latest_height))
+        };
+        let (trusted_header, latest_height) = match chain.await {
+            Ok(chain) => chain,
+            Err(e) => {
+                provider.unpin();
+                chain_read_failed(client, e)?;
+                requested =
+                    wait_for_update(&mut new_blocks, &client.update_requests).await || requested;
+                continue;
+            }
+        };
+        let age = Time::now()
+            .duration_since(trusted_header.header.time)
+            .unwrap_or_default();
 
-        // Get the proposed header from the target light block.
-        let proposed_header = target_light_block.into_header(&trusted_light_block);
+        // Stop for good if the client expired, and escalate as the expiry gets closer.
+        let expiry = thresholds.expiry(age, trusting_period);
+        client
+            .metrics
+            .record(&client.name, trusted_block_height, expiry);
+        let remaining = expiry.remaining.as_secs();
+        match expiry.level {
+            ExpiryLevel::Expired => {
+                return Err(ExpiryError {
+                    client: client.name.clone(),
+                    trusted_height: trusted_block_height,
+                    expiry,
+                }
+                .into());
+            }
+            ExpiryLevel::Critical => error!(
+                "[{}] The client expires in {remaining}s, updating",
+                client.name
+            ),
+            ExpiryLevel::Warning => warn!(
+                "[{}] The client expires in {remaining}s, updating",
+                client.name
+            ),
+            ExpiryLevel::UpdateDue => info!(
+                "[{}] The client expires in {remaining}s, updating",
+                client.name
+            ),
+            ExpiryLevel::Healthy => {}
+        }
 
-        let contract_env = Env {
-            chainId: trusted_light_block.chain_id()?.to_string(),
-            trustThreshold: contract_client_state.trustLevel,
-            trustingPeriod: contract_client_state.trustingPeriod,
-            now: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)?
-                .as_secs(),
+        let status = ClientStatus {
+            trusted_height: trusted_block_height,
+            latest_height,
+            age,
+            trusting_period,
+            requested,
+            expiring: expiry.level >= ExpiryLevel::UpdateDue,
+            since_last_update: last_update.map(|time| time.elapsed()),
+        };
+        let target_height = match policy.decide(&status) {
+            Decision::Update {
+                target_height,
+                reason,
+            } => {
+                info!(
+                    "[{}] Updating from block {trusted_block_height} to block {target_height}: \
+                     {reason}",
+                    client.name
+                );
+                target_height
+            }
+            Decision::Skip(reason) => {
+                provider.unpin();
+                debug!("[{}] Not updating: {reason}", client.name);
+                if args.only_once {
+                    info!(
+                        "[{}] Exiting because '--only-once' flag is set.",
+                        client.name
+                    );
+                    return Ok(());
+                }
+                requested = wait_for_update(&mut new_blocks, &client.update_requests).await;
+                continue;
+            }
         };
 
-        // Generate a proof of the transition from the trusted block to the target block.
-        let proof_data =
-            prover.generate_proof(&trusted_consensus_state, &proposed_header, &contract_env);
+        let trusted_light_block = match provider.get_light_block(Some(trusted_block_height)).await {
+            Ok(trusted_light_block) => trusted_light_block,
+            Err(e) => {
+                provider.unpin();
+                chain_read_failed(client, e)?;
+                requested =
+                    wait_for_update(&mut new_blocks, &client.update_requests).await || requested;
+                continue;
+            }
+        };
+        // The conflicting light blocks of the witnesses must verify from the trusted light block.
+        provider.trust(&trusted_light_block, &options);
 
-        let update_msg = MsgUpdateClient {
-            sp1Proof: SP1Proof::new(
-                &prover.vkey.bytes32(),
-                proof_data.bytes(),
-                proof_data.public_values.to_vec(),
-            ),
+        // Find intermediate heights if the trust threshold is not met in a single step, from the
+        // same endpoint. Refuse to prove if a witness has a conflicting header, and keep the
+        // evidence.
+        let trace = async {
+            if args.furthest_only {
+                let light_block = bisection::furthest_verifiable(
+                    provider,
+                    &trusted_light_block,
+                    target_height,
+                    &options,
+                )
+                .await?;
+                return anyhow::Ok(vec![light_block]);
+            }
+            let target_light_block = provider.get_light_block(Some(target_height)).await?;
+            bisection::verification_trace(
+                provider,
+                &trusted_light_block,
+                target_light_block,
+                &options,
+                args.max_updates,
+            )
+            .await
+        };
+        let trace = trace.await;
+        provider.unpin();
+        let trace = match trace {
+            Ok(trace) => trace,
+            Err(e) => {
+                if let Some(fork) = e.downcast_ref::<ForkDetected>() {
+                    let evidence = fork.misbehaviour_evidence(&trusted_light_block)?;
+                    let path = args.evidence_dir.join(format!(
+                        "fork-evidence-{}-{}.json",
+                        file_name_safe(&client.name),
+                        fork.height()
+                    ));
+                    fs::create_dir_all(&args.evidence_dir)?;
+                    fs::write(&path, serde_json::to_string_pretty(&evidence)?)?;
+                    error!(
+                        "[{}] Misbehaviour evidence written to {}",
+                        client.name,
+                        path.display()
+                    );
+                    return Err(e.context("refusing to prove"));
+                }
+                chain_read_failed(client, e)?;
+                requested =
+                    wait_for_update(&mut new_blocks, &client.update_requests).await || requested;
+                continue;
+            }
         };
+        if trace.len() > 1 {
+            info!(
+                "[{}] Updating through {} intermediate heights to block {}.",
+                client.name,
+                trace.len() - 1,
+                target_height
+            );
+        }
 
-        contract
-            .updateClient(update_msg.abi_encode().into())
-            .send()
-            .await?
-            .watch()
-            .await?;
+        let mut trusted_light_block = trusted_light_block;
+        for light_block in trace {
+            let new_height = light_block.height().value();
+            let job = ProofJob::update_client(
+                update_client_vkey.clone(),
+                &trusted_light_block,
+                light_block.clone(),
+                contract_client_state.trustLevel.clone(),
+                contract_client_state.trustingPeriod,
+            )?;
 
-        info!(
-            "Updated the ICS-07 Tendermint light client at address {} from block {} to block {}.",
-            contract_address, trusted_block_height, target_height
-        );
+            // Generate a proof of the transition from the trusted block to the new block.
+            let proof_data = if let Some(coordinator) = &coordinator {
+                coordinator
+                    .prove(&job, &prover, args.remote_options.poll_interval())
+                    .await?
+            } else {
+                tokio::task::block_in_place(|| prover.prove(job.program, job.sp1_stdin()))?
+            };
+
+            let update_msg = MsgUpdateClient {
+                sp1Proof: SP1Proof::new(
+                    &update_client_vkey,
+                    proof_data.bytes(),
+                    proof_data.public_values.to_vec(),
+                ),
+            };
+
+            contract
+                .updateClient(update_msg.abi_encode().into())
+                .send()
+                .await?
+                .watch()
+                .await?;
+
+            info!(
+                "[{}] Updated the ICS-07 Tendermint light client at address {} from block {} to block {}.",
+                client.name,
+                contract_address,
+                trusted_light_block.height(),
+                new_height
+            );
+            trusted_light_block = light_block;
+        }
+        last_update = Some(Instant::now());
 
         if args.only_once {
-            info!("Exiting because '--only-once' flag is set.");
+            info!(
+                "[{}] Exiting because '--only-once' flag is set.",
+                client.name
+            );
             return Ok(());
         }
 
-        // Sleep for 60 seconds.
-        debug!("sleeping for 60 seconds");
-        tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+        requested = wait_for_update(&mut new_blocks, &client.update_requests).await;
     }
 }
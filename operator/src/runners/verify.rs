@@ -0,0 +1,328 @@
+//! Contains the runner for the `verify` command.
+
+use std::{fmt::Display, path::Path};
+
+use crate::{
+    cli::command::verify::Args,
+    job::ProofJobResult,
+    programs::{registry::ProgramRegistry, ProgramId},
+    runners::{
+        fixtures::{
+            membership::SP1ICS07MembershipFixture, update_client::SP1ICS07UpdateClientFixture,
+        },
+        genesis::SP1ICS07TendermintGenesis,
+    },
+};
+use alloy_sol_types::SolValue;
+use sp1_ics07_tendermint_solidity::sp1_ics07_tendermint::{
+    ClientState, ConsensusState, MembershipOutput, MembershipProof, MsgUpdateClient,
+    SP1MembershipAndUpdateClientProof, SP1MembershipProof, SP1Proof, UcAndMembershipOutput,
+    UpdateClientOutput,
+};
+use sp1_sdk::{HashableKey, ProverClient, SP1ProofWithPublicValues};
+
+/// Where the proof to verify was decoded from.
+enum Source {
+    /// An update client fixture.
+    UpdateClientFixture(SP1ICS07UpdateClientFixture),
+    /// A membership or update client and membership fixture. Membership proofs carry the trusted
+    /// consensus state they are based on.
+    MembershipFixture(SP1ICS07MembershipFixture, Option<ConsensusState>),
+    /// A bare update client message.
+    UpdateMsg,
+    /// A proof job result.
+    JobResult,
+}
+
+impl Source {
+    const fn genesis(&self) -> Option<&SP1ICS07TendermintGenesis> {
+        match self {
+            Self::UpdateClientFixture(fixture) => Some(&fixture.genesis),
+            Self::MembershipFixture(fixture, _) => Some(&fixture.genesis),
+            Self::UpdateMsg | Self::JobResult => None,
+        }
+    }
+}
+
+/// A proof decoded from the input.
+struct DecodedInput {
+    program: ProgramId,
+    sp1_proof: SP1Proof,
+    /// The full proof, which can be verified by the SP1 SDK.
+    proof_with_public_values: Option<SP1ProofWithPublicValues>,
+    source: Source,
+}
+
+/// The mismatches found while verifying.
+#[derive(Default)]
+struct Report {
+    mismatches: Vec<String>,
+}
+
+impl Report {
+    fn check(&mut self, what: &str, expected: &dyn Display, found: &dyn Display) {
+        let (expected, found) = (expected.to_string(), found.to_string());
+        if expected.eq_ignore_ascii_case(&found) {
+            println!("{what}: ok");
+        } else {
+            println!("{what}: MISMATCH");
+            self.mismatches
+                .push(format!("{what}: expected {expected}, found {found}"));
+        }
+    }
+
+    fn mismatch(&mut self, message: String) {
+        println!("{message}");
+        self.mismatches.push(message);
+    }
+}
+
+/// Verifies a fixture, an update client message or a proof job result offline: checks the
+/// verifying key and the SP1 proof, prints the public values and cross-checks them against the
+/// genesis data of fixtures.
+#[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+pub fn run(args: &Args) -> anyhow::Result<()> {
+    let input = if Path::new(&args.input).is_file() {
+        std::fs::read_to_string(&args.input)?
+    } else {
+        args.input.clone()
+    };
+    let decoded = decode_input(input.trim())?;
+    let program = decoded.program;
+    let vkey = decoded.sp1_proof.vKey.to_string();
+    let public_values = decoded.sp1_proof.publicValues.as_ref();
+
+    let registry = ProgramRegistry::load_or_embedded(args.program_options.programs.as_deref())?;
+    let program_set = registry.select(
+        args.program_options.program_version.as_deref(),
+        program,
+        &vkey,
+    )?;
+    let program_vkey = program_set
+        .vkey(program)
+        .ok_or_else(|| anyhow::anyhow!("program {program} not found"))?;
+
+    println!("Program: {program}");
+    println!("Programs version: {}", program_set.version);
+
+    let mut report = Report::default();
+    report.check("verifying key", &program_vkey.bytes32(), &vkey);
+    if let Some(genesis) = decoded.source.genesis() {
+        report.check("genesis verifying key", &genesis.vkey(program), &vkey);
+    }
+
+    if let Some(proof) = &decoded.proof_with_public_values {
+        report.check(
+            "public values",
+            &hex::encode(proof.public_values.as_slice()),
+            &hex::encode(public_values),
+        );
+        report.check(
+            "proof bytes",
+            &hex::encode(proof.bytes()),
+            &hex::encode(&decoded.sp1_proof.proof),
+        );
+        match ProverClient::new().verify(proof, program_vkey) {
+            Ok(()) => println!("SP1 proof: ok"),
+            Err(e) => report.mismatch(format!("SP1 proof: invalid: {e}")),
+        }
+    } else {
+        // The encoded PLONK proof cannot be turned back into the proof verified by the SDK.
+        report.mismatch(
+            "SP1 proof: NOT VERIFIED: the input only contains the encoded proof, which can only \
+             be verified by the SP1 verifier contract"
+                .to_string(),
+        );
+    }
+
+    match program {
+        ProgramId::UpdateClient => {
+            let output = UpdateClientOutput::abi_decode(public_values, true)?;
+            print_public_values(&output)?;
+            if let Source::UpdateClientFixture(fixture) = &decoded.source {
+                check_update_client_output(&mut report, &fixture.genesis, &output)?;
+                report.check(
+                    "target consensus state",
+                    &hex::encode(&fixture.target_consensus_state),
+                    &hex::encode(output.newConsensusState.abi_encode()),
+                );
+                report.check(
+                    "target height",
+                    &fixture.target_height,
+                    &output.newHeight.revisionHeight,
+                );
+            }
+        }
+        ProgramId::Membership => {
+            let output = MembershipOutput::abi_decode(public_values, true)?;
+            print_public_values(&output)?;
+            if let Source::MembershipFixture(fixture, trusted_consensus_state) = &decoded.source {
+                let trusted_consensus_state = trusted_consensus_state
+                    .as_ref()
+                    .expect("membership proofs carry the trusted consensus state");
+                let client_state =
+                    ClientState::abi_decode(&fixture.genesis.trusted_client_state, false)?;
+                report.check(
+                    "trusted consensus state",
+                    &hex::encode(&fixture.genesis.trusted_consensus_state),
+                    &hex::encode(trusted_consensus_state.abi_encode()),
+                );
+                report.check(
+                    "commitment root",
+                    &trusted_consensus_state.root,
+                    &output.commitmentRoot,
+                );
+                report.check(
+                    "proof height",
+                    &hex::encode(client_state.latestHeight.abi_encode()),
+                    &hex::encode(&fixture.proof_height),
+                );
+            }
+        }
+        ProgramId::UpdateClientAndMembership => {
+            let output = UcAndMembershipOutput::abi_decode(public_values, true)?;
+            print_public_values(&output)?;
+            if let Source::MembershipFixture(fixture, _) = &decoded.source {
+                check_update_client_output(
+                    &mut report,
+                    &fixture.genesis,
+                    &output.updateClientOutput,
+                )?;
+                report.check(
+                    "proof height",
+                    &hex::encode(output.updateClientOutput.newHeight.abi_encode()),
+                    &hex::encode(&fixture.proof_height),
+                );
+            }
+        }
+    }
+
+    if !report.mismatches.is_empty() {
+        anyhow::bail!(
+            "verification of the {program} proof failed:\n{}",
+            report.mismatches.join("\n")
+        );
+    }
+
+    Ok(())
+}
+
+/// Decodes a fixture or a proof job result in JSON, or a hex encoded update client message.
+fn decode_input(input: &str) -> anyhow::Result<DecodedInput> {
+    let Ok(json) = serde_json::from_str::<serde_json::Value>(input) else {
+        let msg = MsgUpdateClient::abi_decode(
+            &hex::decode(input.strip_prefix("0x").unwrap_or(input))?,
+            true,
+        )?;
+        return Ok(DecodedInput {
+            program: ProgramId::UpdateClient,
+            sp1_proof: msg.sp1Proof,
+            proof_with_public_values: None,
+            source: Source::UpdateMsg,
+        });
+    };
+
+    if json.get("updateMsg").is_some() {
+        let fixture: SP1ICS07UpdateClientFixture = serde_json::from_value(json)?;
+        let msg = MsgUpdateClient::abi_decode(&fixture.update_msg, true)?;
+        return Ok(DecodedInput {
+            program: ProgramId::UpdateClient,
+            sp1_proof: msg.sp1Proof,
+            proof_with_public_values: decode_proof(fixture.proof_with_public_values.as_deref())?,
+            source: Source::UpdateClientFixture(fixture),
+        });
+    }
+
+    if json.get("membershipProof").is_some() {
+        let fixture: SP1ICS07MembershipFixture = serde_json::from_value(json)?;
+        let membership_proof = MembershipProof::abi_decode(&fixture.membership_proof, true)?;
+        let (program, sp1_proof, trusted_consensus_state) = match membership_proof.proofType {
+            0 => {
+                let proof = SP1MembershipProof::abi_decode(&membership_proof.proof, true)?;
+                (
+                    ProgramId::Membership,
+                    proof.sp1Proof,
+                    Some(proof.trustedConsensusState),
+                )
+            }
+            1 => {
+                let proof =
+                    SP1MembershipAndUpdateClientProof::abi_decode(&membership_proof.proof, true)?;
+                (ProgramId::UpdateClientAndMembership, proof.sp1Proof, None)
+            }
+            proof_type => anyhow::bail!("unknown membership proof type {proof_type}"),
+        };
+        return Ok(DecodedInput {
+            program,
+            sp1_proof,
+            proof_with_public_values: decode_proof(fixture.proof_with_public_values.as_deref())?,
+            source: Source::MembershipFixture(fixture, trusted_consensus_state),
+        });
+    }
+
+    if json.get("job").is_some() {
+        let result: ProofJobResult = serde_json::from_value(json)?;
+        let proof = result.sp1_proof()?;
+        return Ok(DecodedInput {
+            program: result.job.program,
+            sp1_proof: SP1Proof::new(
+                &result.job.vkey,
+                proof.bytes(),
+                proof.public_values.to_vec(),
+            ),
+            proof_with_public_values: Some(proof),
+            source: Source::JobResult,
+        });
+    }
+
+    anyhow::bail!("unrecognized input, expected a fixture or a proof job result")
+}
+
+fn decode_proof(proof: Option<&[u8]>) -> anyhow::Result<Option<SP1ProofWithPublicValues>> {
+    Ok(proof
+        .map(bincode::deserialize::<SP1ProofWithPublicValues>)
+        .transpose()?)
+}
+
+fn print_public_values(output: &impl serde::Serialize) -> anyhow::Result<()> {
+    println!("Public values:\n{}", serde_json::to_string_pretty(output)?);
+    Ok(())
+}
+
+/// Cross-checks the update client output against the genesis data.
+fn check_update_client_output(
+    report: &mut Report,
+    genesis: &SP1ICS07TendermintGenesis,
+    output: &UpdateClientOutput,
+) -> anyhow::Result<()> {
+    let client_state = ClientState::abi_decode(&genesis.trusted_client_state, false)?;
+
+    report.check(
+        "trusted consensus state",
+        &hex::encode(&genesis.trusted_consensus_state),
+        &hex::encode(output.trustedConsensusState.abi_encode()),
+    );
+    report.check(
+        "trusted height",
+        &hex::encode(client_state.latestHeight.abi_encode()),
+        &hex::encode(output.trustedHeight.abi_encode()),
+    );
+    report.check("chain id", &client_state.chainId, &output.env.chainId);
+    report.check(
+        "trust threshold",
+        &format!(
+            "{}/{}",
+            client_state.trustLevel.numerator, client_state.trustLevel.denominator
+        ),
+        &format!(
+            "{}/{}",
+            output.env.trustThreshold.numerator, output.env.trustThreshold.denominator
+        ),
+    );
+    report.check(
+        "trusting period",
+        &client_state.trustingPeriod,
+        &output.env.trustingPeriod,
+    );
+    Ok(())
+}
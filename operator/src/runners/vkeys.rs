@@ -0,0 +1,35 @@
+//! Contains the runner for the `vkeys` command.
+
+use crate::{
+    cli::command::vkeys::Args,
+    programs::{
+        registry::{ProgramRegistry, EMBEDDED_VERSION},
+        ProgramId,
+    },
+    runners::genesis::SP1ICS07TendermintGenesis,
+};
+use sp1_sdk::HashableKey;
+
+/// Prints the verifying keys of the programs and cross-checks them against the genesis file.
+#[allow(clippy::missing_errors_doc)]
+pub fn run(args: &Args) -> anyhow::Result<()> {
+    let registry = ProgramRegistry::load_or_embedded(args.program_options.programs.as_deref())?;
+    let program_set = registry.get_or_err(
+        args.program_options
+            .program_version
+            .as_deref()
+            .unwrap_or(EMBEDDED_VERSION),
+    )?;
+
+    for program in ProgramId::ALL {
+        let vkey = program_set
+            .vkey(program)
+            .ok_or_else(|| anyhow::anyhow!("program {program} not found"))?
+            .bytes32();
+        println!("{program}: {vkey}");
+    }
+
+    SP1ICS07TendermintGenesis::from_file(&args.genesis)?
+        .check_vkeys(program_set)
+        .map_err(|e| e.context(format!("cross-check against {}", args.genesis.display())))
+}
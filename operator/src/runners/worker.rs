@@ -0,0 +1,158 @@
+//! Contains the runner for the `worker` command.
+
+use std::{collections::HashMap, future::Future, time::Duration};
+
+use crate::{
+    cli::command::worker::Args,
+    job::ProofJob,
+    programs::{registry::ProgramRegistry, ProgramId},
+    prover::SP1ICS07TendermintMultiProver,
+    remote::{client::CoordinatorClient, JobOutcome},
+};
+use reqwest::StatusCode;
+use sp1_sdk::HashableKey;
+
+/// The first delay before retrying a failed request to the coordinator.
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+/// The longest delay before retrying a failed request to the coordinator.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Runs a worker that proves the jobs assigned by the coordinator.
+#[allow(clippy::missing_errors_doc, clippy::missing_panics_doc)]
+pub async fn run(args: Args) -> anyhow::Result<()> {
+    let client = CoordinatorClient::new(&args.coordinator, args.token.as_deref())?;
+    let registry = ProgramRegistry::load_or_embedded(args.program_options.programs.as_deref())?;
+    let name = args
+        .name
+        .unwrap_or_else(|| format!("worker-{}", std::process::id()));
+    let poll_interval = Duration::from_secs(args.poll_interval);
+
+    let mut worker_id = register(&client, &name).await?;
+
+    let mut prover = SP1ICS07TendermintMultiProver::empty();
+    if let Some(cache) = args.cache_options.open()? {
+        log::info!("Using the proof cache at {}", cache.dir().display());
+        prover = prover.with_cache(cache);
+    }
+    // The vkeys of the programs currently registered in the prover.
+    let mut registered: HashMap<ProgramId, String> = HashMap::new();
+
+    loop {
+        let assigned = match retry("get the next job", || client.next_job(worker_id)).await {
+            Ok(Some(assigned)) => assigned,
+            Ok(None) => {
+                tokio::time::sleep(poll_interval).await;
+                continue;
+            }
+            // The coordinator restarted and forgot the worker.
+            Err(e) if rejected_with(&e) == Some(StatusCode::NOT_FOUND) => {
+                log::warn!("The coordinator does not know worker {worker_id}, registering again");
+                worker_id = register(&client, &name).await?;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        log::info!("Proving {} job {}", assigned.job.program, assigned.job_id);
+
+        let outcome = match prove(
+            &registry,
+            args.program_options.program_version.as_deref(),
+            &mut prover,
+            &mut registered,
+            &assigned.job,
+        ) {
+            Ok((vkey, proof)) => JobOutcome::Proven { vkey, proof },
+            Err(e) => JobOutcome::Failed {
+                error: format!("{e:#}"),
+            },
+        };
+
+        // The job was reassigned or dropped in the meantime, the next one can be proven.
+        if let Err(e) = retry("report the job", || {
+            client.report_job(worker_id, assigned.job_id, outcome.clone())
+        })
+        .await
+        {
+            log::warn!(
+                "The coordinator rejected the report of job {}: {e:#}",
+                assigned.job_id
+            );
+        }
+    }
+}
+
+/// Registers the worker with the coordinator, and returns its identifier.
+async fn register(client: &CoordinatorClient, name: &str) -> anyhow::Result<u64> {
+    let worker_id = retry("register with the coordinator", || {
+        client.register_worker(name)
+    })
+    .await?;
+    log::info!("Registered with the coordinator as worker {worker_id}");
+    Ok(worker_id)
+}
+
+/// Retries `request` with an exponential backoff while the coordinator cannot be reached or
+/// fails, so that the worker outlives a restart of the coordinator. Returns the error only if the
+/// coordinator rejects the request.
+async fn retry<T, F>(what: &str, mut request: impl FnMut() -> F) -> anyhow::Result<T>
+where
+    F: Future<Output = anyhow::Result<T>>,
+{
+    let mut backoff = MIN_BACKOFF;
+    loop {
+        match request().await {
+            Ok(value) => return Ok(value),
+            Err(e) if rejected_with(&e).is_some() => return Err(e),
+            Err(e) => {
+                log::warn!(
+                    "Failed to {what}, retrying in {}s: {e:#}",
+                    backoff.as_secs()
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_BACKOFF);
+            }
+        }
+    }
+}
+
+/// Gets the status of the response if the coordinator rejected the request.
+fn rejected_with(error: &anyhow::Error) -> Option<StatusCode> {
+    error
+        .downcast_ref::<reqwest::Error>()
+        .and_then(reqwest::Error::status)
+        .filter(StatusCode::is_client_error)
+}
+
+/// Proves `job`, registering the matching program in `prover` first if needed.
+/// Returns the verifying key and the bincode encoded proof.
+fn prove(
+    registry: &ProgramRegistry,
+    version: Option<&str>,
+    prover: &mut SP1ICS07TendermintMultiProver,
+    registered: &mut HashMap<ProgramId, String>,
+    job: &ProofJob,
+) -> anyhow::Result<(String, Vec<u8>)> {
+    let program_set = registry.select(version, job.program, &job.vkey)?;
+    let vkey = program_set
+        .vkey(job.program)
+        .ok_or_else(|| anyhow::anyhow!("program {} not found", job.program))?
+        .bytes32();
+    // The contract the job was created for only accepts proofs of its own verifying key.
+    anyhow::ensure!(
+        vkey.eq_ignore_ascii_case(&job.vkey),
+        "programs version '{}' has vkey {vkey}, but the job was created for vkey {}",
+        program_set.version,
+        job.vkey
+    );
+
+    if registered.get(&job.program) != Some(&vkey) {
+        let elf = program_set
+            .elf(job.program)
+            .ok_or_else(|| anyhow::anyhow!("program {} not found", job.program))?;
+        prover.register_elf(job.program, elf);
+        registered.insert(job.program, vkey.clone());
+    }
+
+    let proof = tokio::task::block_in_place(|| prover.prove(job.program, job.sp1_stdin()))?;
+    Ok((vkey, bincode::serialize(&proof)?))
+}
@@ -0,0 +1,214 @@
+//! The policies deciding when the operator updates a client, and the update requests.
+
+use std::{collections::HashMap, net::SocketAddr, sync::Arc, time::Duration};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    routing::post,
+    Router,
+};
+use tokio::sync::Notify;
+
+/// When the operator updates a client, and how far.
+///
+/// Without any trigger, the client is updated to every new block. Otherwise, it is updated when
+/// any of the triggers fires, or only on request with `on_request`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TriggerPolicy {
+    /// Update when the chain is more than this number of blocks ahead of the client.
+    pub max_height_lag: Option<u32>,
+    /// Update when the latest consensus state is older than this fraction of the trusting period.
+    pub trusting_period_fraction: Option<f64>,
+    /// Update only on request, ignoring the other triggers.
+    pub on_request: bool,
+    /// The minimum time between two updates, unless requested.
+    pub min_interval: Option<Duration>,
+    /// The maximum number of blocks the client is updated by at once.
+    pub max_height_jump: Option<u32>,
+}
+
+/// The state of a client and the chain, that the triggers are evaluated against.
+#[derive(Clone, Copy, Debug)]
+pub struct ClientStatus {
+    /// The latest height of the client.
+    pub trusted_height: u32,
+    /// The latest height of the chain.
+    pub latest_height: u32,
+    /// The age of the latest consensus state of the client.
+    pub age: Duration,
+    /// The trusting period of the client.
+    pub trusting_period: Duration,
+    /// Whether an update was requested.
+    pub requested: bool,
+    /// Whether the client must be updated before its trusting period elapses.
+    pub expiring: bool,
+    /// The time since the last update by the operator, if any.
+    pub since_last_update: Option<Duration>,
+}
+
+/// The decision of a [`TriggerPolicy`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Decision {
+    /// Update the client to `target_height`.
+    Update {
+        /// The height to update the client to.
+        target_height: u32,
+        /// Why the client is updated.
+        reason: String,
+    },
+    /// Do not update the client, for this reason.
+    Skip(String),
+}
+
+impl TriggerPolicy {
+    /// Decides whether to update a client in `status`, and to which height.
+    #[must_use]
+    pub fn decide(&self, status: &ClientStatus) -> Decision {
+        let lag = status.latest_height.saturating_sub(status.trusted_height);
+        if lag == 0 {
+            return Decision::Skip("the client is up to date".to_string());
+        }
+
+        let reason = if status.expiring {
+            "the trusting period is running out".to_string()
+        } else if status.requested {
+            "requested".to_string()
+        } else if self.on_request {
+            return Decision::Skip("waiting for a request".to_string());
+        } else {
+            if let (Some(min_interval), Some(since_last_update)) =
+                (self.min_interval, status.since_last_update)
+            {
+                if since_last_update < min_interval {
+                    return Decision::Skip(format!(
+                        "last update {}s ago, minimum interval {}s",
+                        since_last_update.as_secs(),
+                        min_interval.as_secs()
+                    ));
+                }
+            }
+            match self.fired_trigger(lag, status) {
+                Ok(reason) => reason,
+                Err(reason) => return Decision::Skip(reason),
+            }
+        };
+
+        let target_height = self.max_height_jump.map_or(status.latest_height, |jump| {
+            status
+                .latest_height
+                .min(status.trusted_height.saturating_add(jump))
+        });
+        Decision::Update {
+            target_height,
+            reason,
+        }
+    }
+
+    /// Gets the trigger that fired, or why none did.
+    fn fired_trigger(&self, lag: u32, status: &ClientStatus) -> Result<String, String> {
+        if self.max_height_lag.is_none() && self.trusting_period_fraction.is_none() {
+            return Ok(format!("{lag} new blocks"));
+        }
+        if let Some(max_height_lag) = self.max_height_lag {
+            if lag > max_height_lag {
+                return Ok(format!("height lag {lag} above {max_height_lag}"));
+            }
+        }
+        if let Some(fraction) = self.trusting_period_fraction {
+            let max_age = status.trusting_period.mul_f64(fraction);
+            if status.age > max_age {
+                return Ok(format!(
+                    "consensus state age {}s above {}s",
+                    status.age.as_secs(),
+                    max_age.as_secs()
+                ));
+            }
+        }
+        Err(format!(
+            "height lag {lag}, consensus state age {}s",
+            status.age.as_secs()
+        ))
+    }
+}
+
+/// Checks that a trusting period fraction is in (0, 1], since the client expires once its
+/// consensus state is older than the trusting period.
+///
+/// # Errors
+/// Returns an error if the fraction is out of range.
+pub fn check_trusting_period_fraction(fraction: f64) -> anyhow::Result<f64> {
+    anyhow::ensure!(
+        fraction > 0.0 && fraction <= 1.0,
+        "the trusting period fraction must be in (0, 1], got {fraction}"
+    );
+    Ok(fraction)
+}
+
+/// The update requests of the clients driven by the operator, served over HTTP:
+/// - `POST /update/<name>` requests an update of the client `name`.
+/// - `POST /update` requests an update of every client.
+///
+/// A request made while a client is busy is kept until the client is ready.
+#[derive(Debug, Default)]
+pub struct UpdateRequests {
+    clients: HashMap<String, Arc<Notify>>,
+}
+
+impl UpdateRequests {
+    /// Creates the update requests of the clients `names`.
+    #[must_use]
+    pub fn new(names: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            clients: names
+                .into_iter()
+                .map(|name| (name, Arc::new(Notify::new())))
+                .collect(),
+        }
+    }
+
+    /// Gets the notifications of the update requests of the client `name`. They never fire for an
+    /// unknown client.
+    #[must_use]
+    pub fn client(&self, name: &str) -> Arc<Notify> {
+        self.clients.get(name).cloned().unwrap_or_default()
+    }
+
+    /// Serve the update requests on `addr`.
+    ///
+    /// # Errors
+    /// Returns an error if the address cannot be bound or the server fails.
+    pub async fn serve(self: Arc<Self>, addr: SocketAddr) -> anyhow::Result<()> {
+        let app = Router::new()
+            .route("/update", post(request_all))
+            .route("/update/:name", post(request_client))
+            .with_state(self);
+
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        log::info!("Listening for update requests on {addr}");
+        axum::serve(listener, app).await?;
+        Ok(())
+    }
+}
+
+#[allow(clippy::unused_async)]
+async fn request_all(State(requests): State<Arc<UpdateRequests>>) -> StatusCode {
+    log::info!("Update of every client requested");
+    for notify in requests.clients.values() {
+        notify.notify_one();
+    }
+    StatusCode::ACCEPTED
+}
+
+#[allow(clippy::unused_async)]
+async fn request_client(
+    State(requests): State<Arc<UpdateRequests>>,
+    Path(name): Path<String>,
+) -> StatusCode {
+    let Some(notify) = requests.clients.get(&name) else {
+        return StatusCode::NOT_FOUND;
+    };
+    log::info!("[{name}] Update requested");
+    notify.notify_one();
+    StatusCode::ACCEPTED
+}
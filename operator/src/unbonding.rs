@@ -0,0 +1,162 @@
+//! Sources of the unbonding period of the chain, for the chains that do not answer the Cosmos SDK
+//! staking query over ABCI.
+
+use std::fmt;
+
+use anyhow::{Context, Result};
+use cosmos_sdk_proto::tendermint::google::protobuf::Duration;
+use prost::Message;
+use serde::Deserialize;
+
+use crate::rpc::{LightBlockProvider, TendermintRpcExt};
+
+/// The ABCI query path of the Interchain Security consumer parameters.
+pub const CONSUMER_PARAMS_PATH: &str = "/interchain_security.ccv.consumer.v1.Query/QueryParams";
+
+/// The gRPC-gateway path of the Cosmos SDK staking parameters.
+pub const STAKING_PARAMS_REST_PATH: &str = "/cosmos/staking/v1beta1/params";
+
+/// Where the unbonding period of the chain is taken from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum UnbondingPeriodSource {
+    /// The given period, in seconds.
+    Explicit(u32),
+    /// The Cosmos SDK staking parameters, queried over ABCI.
+    #[default]
+    StakingParams,
+    /// The Cosmos SDK staking parameters, queried from the gRPC-gateway at this URL.
+    Rest(String),
+    /// The Interchain Security consumer parameters, queried over ABCI.
+    ConsumerParams,
+}
+
+impl fmt::Display for UnbondingPeriodSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Explicit(_) => write!(f, "--unbonding-period"),
+            Self::StakingParams => write!(f, "the staking parameters over ABCI"),
+            Self::Rest(url) => write!(f, "the staking parameters at {url}"),
+            Self::ConsumerParams => write!(f, "the ICS consumer parameters over ABCI"),
+        }
+    }
+}
+
+impl UnbondingPeriodSource {
+    /// Gets the unbonding period in seconds from the source.
+    ///
+    /// # Errors
+    /// Returns an error if the query fails, or the period is missing or zero.
+    pub async fn unbonding_period(
+        &self,
+        provider: &(impl LightBlockProvider + ?Sized),
+    ) -> Result<u32> {
+        let unbonding_period = match self {
+            Self::Explicit(seconds) => *seconds,
+            Self::StakingParams => provider
+                .sdk_staking_params()
+                .await
+                .and_then(|params| duration_seconds(params.unbonding_time))
+                .context(
+                    "failed to query the staking parameters, pass the unbonding period with \
+                     --unbonding-period, --staking-rest-url or --ics-consumer",
+                )?,
+            Self::Rest(url) => rest_unbonding_period(url)
+                .await
+                .with_context(|| format!("failed to query the staking parameters at {url}"))?,
+            Self::ConsumerParams => consumer_unbonding_period(provider)
+                .await
+                .context("failed to query the ICS consumer parameters")?,
+        };
+        anyhow::ensure!(
+            unbonding_period > 0,
+            "the unbonding period from {self} is zero"
+        );
+
+        log::info!("Unbonding period: {unbonding_period}s, from {self}");
+        Ok(unbonding_period)
+    }
+
+    /// Gets the unbonding period from the source and the trusting period, which defaults to the
+    /// recommended 2/3 of the unbonding period. Returns `(unbonding_period, trusting_period)`.
+    ///
+    /// # Errors
+    /// Returns an error if the unbonding period cannot be found, or the trusting period is
+    /// greater than it.
+    pub async fn periods(
+        &self,
+        provider: &(impl LightBlockProvider + ?Sized),
+        trusting_period: Option<u32>,
+    ) -> Result<(u32, u32)> {
+        let unbonding_period = self.unbonding_period(provider).await?;
+        let trusting_period = trusting_period.unwrap_or(2 * (unbonding_period / 3));
+        anyhow::ensure!(
+            trusting_period <= unbonding_period,
+            "Trusting period of {trusting_period}s cannot be greater than the unbonding period \
+             of {unbonding_period}s from {self}"
+        );
+        Ok((unbonding_period, trusting_period))
+    }
+}
+
+/// The response of the [`CONSUMER_PARAMS_PATH`] query, with only the fields in use.
+#[derive(Clone, PartialEq, Message)]
+struct ConsumerQueryParamsResponse {
+    #[prost(message, optional, tag = "1")]
+    params: Option<ConsumerParams>,
+}
+
+/// The Interchain Security consumer parameters, with only the fields in use.
+#[derive(Clone, PartialEq, Message)]
+struct ConsumerParams {
+    #[prost(message, optional, tag = "9")]
+    unbonding_period: Option<Duration>,
+}
+
+/// The gRPC-gateway response of the staking parameters, with only the fields in use.
+#[derive(Deserialize)]
+struct RestStakingParamsResponse {
+    params: RestStakingParams,
+}
+
+#[derive(Deserialize)]
+struct RestStakingParams {
+    unbonding_time: String,
+}
+
+async fn consumer_unbonding_period(provider: &(impl LightBlockProvider + ?Sized)) -> Result<u32> {
+    let response = provider
+        .abci_query(Some(CONSUMER_PARAMS_PATH.to_string()), vec![], None, false)
+        .await?;
+    let params = ConsumerQueryParamsResponse::decode(response.value.as_slice())?
+        .params
+        .context("no consumer parameters found")?;
+    duration_seconds(params.unbonding_period)
+}
+
+async fn rest_unbonding_period(url: &str) -> Result<u32> {
+    let response: RestStakingParamsResponse = reqwest::get(format!(
+        "{}{STAKING_PARAMS_REST_PATH}",
+        url.trim_end_matches('/')
+    ))
+    .await?
+    .error_for_status()?
+    .json()
+    .await?;
+    parse_json_duration(&response.params.unbonding_time)
+}
+
+fn duration_seconds(duration: Option<Duration>) -> Result<u32> {
+    Ok(duration
+        .context("no unbonding period found")?
+        .seconds
+        .try_into()?)
+}
+
+/// Parses a protobuf JSON duration, such as `1814400s` or `1814400.5s`, into whole seconds.
+fn parse_json_duration(duration: &str) -> Result<u32> {
+    duration
+        .strip_suffix('s')
+        .and_then(|seconds| seconds.split('.').next())
+        .and_then(|seconds| seconds.parse().ok())
+        .with_context(|| format!("invalid duration {duration}"))
+}
@@ -0,0 +1,251 @@
+//! The watchdog of the trusting period of the clients, which forces updates before the clients
+//! expire and escalates when they get close to it.
+
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write},
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use axum::{extract::State, routing::get, Router};
+
+/// The exit code of the operator when a client is close to expiry and its update failed.
+pub const EXIT_CODE_CRITICAL: i32 = 2;
+
+/// The exit code of the operator when a client has expired.
+pub const EXIT_CODE_EXPIRED: i32 = 3;
+
+/// The fractions of the trusting period left at which the watchdog escalates.
+#[derive(Clone, Copy, Debug)]
+pub struct ExpiryThresholds {
+    /// Force an update, whatever the trigger policy, below this fraction.
+    pub force_update: f64,
+    /// Log warnings below this fraction.
+    pub warning: f64,
+    /// Log errors below this fraction, and exit with [`EXIT_CODE_CRITICAL`] if the update fails.
+    pub critical: f64,
+}
+
+/// How close a client is to the expiry of its trusting period, from the least to the most
+/// urgent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ExpiryLevel {
+    /// The client is far from expiry.
+    Healthy,
+    /// The client must be updated.
+    UpdateDue,
+    /// The client is getting close to expiry.
+    Warning,
+    /// The client is about to expire.
+    Critical,
+    /// The trusting period has elapsed, the client cannot be updated anymore.
+    Expired,
+}
+
+impl fmt::Display for ExpiryLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Healthy => write!(f, "healthy"),
+            Self::UpdateDue => write!(f, "update due"),
+            Self::Warning => write!(f, "warning"),
+            Self::Critical => write!(f, "critical"),
+            Self::Expired => write!(f, "expired"),
+        }
+    }
+}
+
+/// The expiry of a client, from the age of its latest consensus state.
+#[derive(Clone, Copy, Debug)]
+pub struct Expiry {
+    /// The time left before the trusting period elapses.
+    pub remaining: Duration,
+    /// The trusting period of the client.
+    pub trusting_period: Duration,
+    /// How close the client is to expiry.
+    pub level: ExpiryLevel,
+}
+
+impl ExpiryThresholds {
+    /// Gets the expiry of a client whose latest consensus state is `age` old.
+    #[must_use]
+    pub fn expiry(&self, age: Duration, trusting_period: Duration) -> Expiry {
+        let remaining = trusting_period.saturating_sub(age);
+        let fraction = remaining.as_secs_f64() / trusting_period.as_secs_f64().max(1.0);
+        let level = if remaining.is_zero() {
+            ExpiryLevel::Expired
+        } else if fraction < self.critical {
+            ExpiryLevel::Critical
+        } else if fraction < self.warning {
+            ExpiryLevel::Warning
+        } else if fraction < self.force_update {
+            ExpiryLevel::UpdateDue
+        } else {
+            ExpiryLevel::Healthy
+        };
+        Expiry {
+            remaining,
+            trusting_period,
+            level,
+        }
+    }
+}
+
+/// The error stopping the operator when a client expired, or is about to and could not be
+/// updated.
+#[derive(Debug, Clone)]
+pub struct ExpiryError {
+    /// The name of the client.
+    pub client: String,
+    /// The latest height of the client.
+    pub trusted_height: u32,
+    /// The expiry of the client.
+    pub expiry: Expiry,
+}
+
+impl ExpiryError {
+    /// Gets the exit code of the operator for this error.
+    #[must_use]
+    pub const fn exit_code(&self) -> i32 {
+        match self.expiry.level {
+            ExpiryLevel::Expired => EXIT_CODE_EXPIRED,
+            _ => EXIT_CODE_CRITICAL,
+        }
+    }
+}
+
+impl fmt::Display for ExpiryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.expiry.level == ExpiryLevel::Expired {
+            write!(
+                f,
+                "client {} expired: its consensus state at height {} is older than the trusting \
+                 period of {}s, it must be recovered by governance or replaced",
+                self.client,
+                self.trusted_height,
+                self.expiry.trusting_period.as_secs()
+            )
+        } else {
+            write!(
+                f,
+                "client {} failed to update with {}s left before expiry",
+                self.client,
+                self.expiry.remaining.as_secs()
+            )
+        }
+    }
+}
+
+impl std::error::Error for ExpiryError {}
+
+/// Gets the exit code of the operator for `error`, if it is caused by an [`ExpiryError`].
+#[must_use]
+pub fn exit_code(error: &anyhow::Error) -> Option<i32> {
+    error
+        .downcast_ref::<ExpiryError>()
+        .map(ExpiryError::exit_code)
+}
+
+/// The expiry metrics of the clients, served in the Prometheus text format on `/metrics`. They
+/// also keep the latest expiry of every client, to escalate its failures.
+#[derive(Debug, Default)]
+pub struct ExpiryMetrics {
+    clients: Mutex<BTreeMap<String, (u32, Expiry)>>,
+}
+
+impl ExpiryMetrics {
+    /// Records the latest height and the expiry of the client `name`.
+    pub fn record(&self, name: &str, trusted_height: u32, expiry: Expiry) {
+        self.clients
+            .lock()
+            .expect("expiry metrics poisoned")
+            .insert(name.to_string(), (trusted_height, expiry));
+    }
+
+    /// Renders the metrics in the Prometheus text format.
+    #[must_use]
+    pub fn render(&self) -> String {
+        let clients = self.clients.lock().expect("expiry metrics poisoned");
+        let mut out = String::new();
+        let mut gauge = |name: &str, help: &str, value: &dyn Fn(&(u32, Expiry)) -> String| {
+            let _ = writeln!(out, "# HELP {name} {help}\n# TYPE {name} gauge");
+            for (client, entry) in clients.iter() {
+                let client = escape_label_value(client);
+                let _ = writeln!(out, "{name}{{client=\"{client}\"}} {}", value(entry));
+            }
+        };
+        gauge(
+            "sp1_ics07_tendermint_trusted_height",
+            "The latest height of the client.",
+            &|(height, _)| height.to_string(),
+        );
+        gauge(
+            "sp1_ics07_tendermint_trusting_period_remaining_seconds",
+            "The time left before the trusting period of the client elapses.",
+            &|(_, expiry)| expiry.remaining.as_secs().to_string(),
+        );
+        gauge(
+            "sp1_ics07_tendermint_expiry_level",
+            "0 healthy, 1 update due, 2 warning, 3 critical, 4 expired.",
+            &|(_, expiry)| (expiry.level as u8).to_string(),
+        );
+        out
+    }
+
+    /// Gets the latest recorded expiry of the client `name`.
+    #[must_use]
+    pub fn expiry(&self, name: &str) -> Option<(u32, Expiry)> {
+        self.clients
+            .lock()
+            .expect("expiry metrics poisoned")
+            .get(name)
+            .copied()
+    }
+
+    /// Escalates `error`, the failure of the client `name`, to an [`ExpiryError`] if the client
+    /// was about to expire.
+    #[must_use]
+    pub fn escalate(&self, name: &str, error: anyhow::Error) -> anyhow::Error {
+        match self.expiry(name) {
+            Some((trusted_height, expiry))
+                if expiry.level >= ExpiryLevel::Critical && exit_code(&error).is_none() =>
+            {
+                error.context(ExpiryError {
+                    client: name.to_string(),
+                    trusted_height,
+                    expiry,
+                })
+            }
+            _ => error,
+        }
+    }
+
+    /// Serve the metrics on `addr`.
+    ///
+    /// # Errors
+    /// Returns an error if the address cannot be bound or the server fails.
+    pub async fn serve(self: Arc<Self>, addr: SocketAddr) -> anyhow::Result<()> {
+        let app = Router::new()
+            .route("/metrics", get(metrics))
+            .with_state(self);
+
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        log::info!("Serving metrics on {addr}");
+        axum::serve(listener, app).await?;
+        Ok(())
+    }
+}
+
+/// Escapes a label value of the Prometheus text format.
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[allow(clippy::unused_async)]
+async fn metrics(State(metrics): State<Arc<ExpiryMetrics>>) -> String {
+    metrics.render()
+}
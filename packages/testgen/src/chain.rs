@@ -0,0 +1,524 @@
+//! A synthetic Tendermint chain producing signed light blocks.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    time::Duration,
+};
+
+use anyhow::{Context, Result};
+use ibc_core_commitment_types::merkle::MerkleProof;
+use sha2::{Digest, Sha256};
+use tendermint::{
+    block::{self, header::Version, parts, signed_header::SignedHeader, Commit, CommitSig, Header},
+    chain,
+    merkle::proof::ProofOps,
+    node, validator, vote, AppHash, Hash, Time, Vote,
+};
+use tendermint_light_client_verifier::types::LightBlock;
+
+use crate::{store::Store, validator::TestValidator};
+
+/// The default time between blocks.
+pub const DEFAULT_BLOCK_INTERVAL: Duration = Duration::from_secs(6);
+
+/// The default unbonding period, of three weeks.
+pub const DEFAULT_UNBONDING_PERIOD: Duration = Duration::from_secs(21 * 24 * 60 * 60);
+
+/// The default time of the first block, 2024-01-01T00:00:00Z.
+pub const DEFAULT_GENESIS_TIME: i64 = 1_704_067_200;
+
+/// A synthetic Tendermint chain producing signed light blocks and committing an IBC [`Store`]
+/// at every height.
+///
+/// Changes to the validators are announced in the next validators of the next block and take
+/// effect in the block after it, as in Tendermint. Signatures and time gaps apply to the next
+/// block only. A fork is a clone of the chain that diverges, for example with a different store
+/// or time.
+#[derive(Clone, Debug)]
+pub struct TestChain {
+    chain_id: chain::Id,
+    block_interval: Duration,
+    unbonding_period: Duration,
+    time: Time,
+    validators: Vec<TestValidator>,
+    next_validators: Vec<TestValidator>,
+    absent: BTreeSet<String>,
+    time_gap: Duration,
+    store: Store,
+    committed: BTreeMap<u32, Store>,
+    light_blocks: BTreeMap<u32, LightBlock>,
+}
+
+impl TestChain {
+    /// Creates a new chain without blocks, signed by `validators`.
+    ///
+    /// # Errors
+    /// Returns an error if the chain identifier is invalid or there are no validators.
+    pub fn new(chain_id: &str, validators: Vec<TestValidator>) -> Result<Self> {
+        anyhow::ensure!(!validators.is_empty(), "a chain needs validators");
+        Ok(Self {
+            chain_id: chain_id.parse()?,
+            block_interval: DEFAULT_BLOCK_INTERVAL,
+            unbonding_period: DEFAULT_UNBONDING_PERIOD,
+            time: Time::from_unix_timestamp(DEFAULT_GENESIS_TIME, 0)?,
+            next_validators: validators.clone(),
+            validators,
+            absent: BTreeSet::new(),
+            time_gap: Duration::ZERO,
+            store: Store::default(),
+            committed: BTreeMap::from([(0, Store::default())]),
+            light_blocks: BTreeMap::new(),
+        })
+    }
+
+    /// Sets the time between blocks.
+    #[must_use]
+    pub const fn with_block_interval(mut self, block_interval: Duration) -> Self {
+        self.block_interval = block_interval;
+        self
+    }
+
+    /// Sets the unbonding period reported in the staking parameters.
+    #[must_use]
+    pub const fn with_unbonding_period(mut self, unbonding_period: Duration) -> Self {
+        self.unbonding_period = unbonding_period;
+        self
+    }
+
+    /// Sets the time of the first block.
+    #[must_use]
+    pub const fn with_genesis_time(mut self, time: Time) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// Gets the chain identifier.
+    #[must_use]
+    pub const fn chain_id(&self) -> &chain::Id {
+        &self.chain_id
+    }
+
+    /// Gets the unbonding period.
+    #[must_use]
+    pub const fn unbonding_period(&self) -> Duration {
+        self.unbonding_period
+    }
+
+    /// Gets the height of the latest block, 0 before the first block.
+    #[must_use]
+    pub fn latest_height(&self) -> u32 {
+        self.light_blocks.keys().next_back().copied().unwrap_or(0)
+    }
+
+    /// Gets the light block at `height`.
+    #[must_use]
+    pub fn light_block(&self, height: u32) -> Option<&LightBlock> {
+        self.light_blocks.get(&height)
+    }
+
+    /// Gets all the light blocks, by height.
+    #[must_use]
+    pub const fn light_blocks(&self) -> &BTreeMap<u32, LightBlock> {
+        &self.light_blocks
+    }
+
+    /// Gets the working IBC store, committed with the next block.
+    pub fn store_mut(&mut self) -> &mut Store {
+        &mut self.store
+    }
+
+    /// Creates a fork of the chain: a copy that produces conflicting blocks once it diverges.
+    #[must_use]
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
+
+    /// Sets the power of the validator `name`. A power of 0 removes the validator.
+    ///
+    /// # Errors
+    /// Returns an error if there is no such validator.
+    pub fn set_power(&mut self, name: &str, power: u64) -> Result<()> {
+        let index = self.next_validator_index(name)?;
+        if power == 0 {
+            anyhow::ensure!(
+                self.next_validators.len() > 1,
+                "cannot remove the last validator"
+            );
+            self.next_validators.remove(index);
+        } else {
+            self.next_validators[index] = self.next_validators[index].clone().with_power(power);
+        }
+        Ok(())
+    }
+
+    /// Adds a validator.
+    ///
+    /// # Errors
+    /// Returns an error if a validator with the same name exists.
+    pub fn add_validator(&mut self, validator: TestValidator) -> Result<()> {
+        anyhow::ensure!(
+            self.next_validator_index(validator.name()).is_err(),
+            "validator {} already exists",
+            validator.name()
+        );
+        self.next_validators.push(validator);
+        Ok(())
+    }
+
+    /// Rotates the key of the validator `name` to one derived from `seed`.
+    ///
+    /// # Errors
+    /// Returns an error if there is no such validator.
+    pub fn rotate_key(&mut self, name: &str, seed: &[u8]) -> Result<()> {
+        let index = self.next_validator_index(name)?;
+        self.next_validators[index] = self.next_validators[index].clone().with_seed(seed);
+        Ok(())
+    }
+
+    /// Leaves out the signature of the validator `name` from the next block.
+    pub fn skip_signature(&mut self, name: &str) {
+        self.absent.insert(name.to_string());
+    }
+
+    /// Adds `gap` to the time between the latest and the next block.
+    pub fn advance_time(&mut self, gap: Duration) {
+        self.time_gap += gap;
+    }
+
+    /// Produces `count` blocks.
+    ///
+    /// # Errors
+    /// Returns an error if a block cannot be produced.
+    pub fn produce_blocks(&mut self, count: u32) -> Result<()> {
+        for _ in 0..count {
+            self.produce_block()?;
+        }
+        Ok(())
+    }
+
+    /// Produces the next block, signed by the current validators, and commits the store.
+    ///
+    /// # Errors
+    /// Returns an error if the block cannot be built or signed.
+    pub fn produce_block(&mut self) -> Result<&LightBlock> {
+        let height = self.latest_height() + 1;
+        let interval = if height > 1 {
+            self.block_interval
+        } else {
+            Duration::ZERO
+        };
+        self.time = self
+            .time
+            .checked_add(interval + self.time_gap)
+            .context("block time overflow")?;
+        let validators = validator_set(&self.validators)?;
+        let next_validators = validator_set(&self.next_validators)?;
+
+        let previous_store = &self.committed[&(height - 1)];
+        let last_block_id = self
+            .light_blocks
+            .get(&(height - 1))
+            .map(|light_block| light_block.signed_header.commit.block_id);
+        let header = Header {
+            version: Version { block: 11, app: 1 },
+            chain_id: self.chain_id.clone(),
+            height: height.into(),
+            time: self.time,
+            last_block_id,
+            last_commit_hash: last_block_id.map(|_| placeholder_hash("last commit", height)),
+            data_hash: Some(placeholder_hash("data", height)),
+            validators_hash: validators.hash(),
+            next_validators_hash: next_validators.hash(),
+            consensus_hash: placeholder_hash("consensus params", 0),
+            app_hash: AppHash::try_from(previous_store.app_hash(u64::from(height - 1)).to_vec())?,
+            last_results_hash: Some(placeholder_hash("last results", height)),
+            evidence_hash: Some(placeholder_hash("evidence", height)),
+            proposer_address: validators.validators()[0].address,
+        };
+
+        let block_id = block::Id {
+            hash: header.hash(),
+            part_set_header: parts::Header::new(1, placeholder_hash("parts", height))?,
+        };
+        let commit = Commit {
+            height: header.height,
+            round: block::Round::default(),
+            block_id,
+            signatures: self.sign(&validators, block_id, header.height)?,
+        };
+        let light_block = LightBlock::new(
+            SignedHeader::new(header, commit)?,
+            validators,
+            next_validators,
+            node::Id::new([0; 20]),
+        );
+
+        self.committed.insert(height, self.store.clone());
+        self.light_blocks.insert(height, light_block);
+        self.validators = self.next_validators.clone();
+        self.absent.clear();
+        self.time_gap = Duration::ZERO;
+        Ok(&self.light_blocks[&height])
+    }
+
+    /// Proves `key` in the IBC store committed at `height`, against the app hash of the block at
+    /// `height + 1`, like an ABCI query at `height`. Returns the value, empty if absent, and the
+    /// proof.
+    ///
+    /// # Errors
+    /// Returns an error if no store is committed at `height` or the store is empty.
+    pub fn prove(&self, key: &[u8], height: u32) -> Result<(Vec<u8>, MerkleProof)> {
+        self.committed_store(height)?.prove(key, u64::from(height))
+    }
+
+    /// Proves `key` like [`TestChain::prove`], as the proof operations of an ABCI query.
+    ///
+    /// # Errors
+    /// Returns an error if no store is committed at `height` or the store is empty.
+    pub fn prove_ops(&self, key: &[u8], height: u32) -> Result<(Vec<u8>, ProofOps)> {
+        self.committed_store(height)?
+            .prove_ops(key, u64::from(height))
+    }
+
+    fn committed_store(&self, height: u32) -> Result<&Store> {
+        self.committed
+            .get(&height)
+            .ok_or_else(|| anyhow::anyhow!("no store committed at height {height}"))
+    }
+
+    fn next_validator_index(&self, name: &str) -> Result<usize> {
+        self.next_validators
+            .iter()
+            .position(|validator| validator.name() == name)
+            .ok_or_else(|| anyhow::anyhow!("no validator named {name}"))
+    }
+
+    /// Signs the block `block_id` with the validators of `validator_set`, in its order.
+    fn sign(
+        &self,
+        validator_set: &validator::Set,
+        block_id: block::Id,
+        height: block::Height,
+    ) -> Result<Vec<CommitSig>> {
+        validator_set
+            .validators()
+            .iter()
+            .enumerate()
+            .map(|(index, info)| {
+                let validator = self
+                    .validators
+                    .iter()
+                    .find(|validator| validator.address() == info.address)
+                    .expect("the validator set is built from the validators");
+                if self.absent.contains(validator.name()) {
+                    return Ok(CommitSig::BlockIdFlagAbsent);
+                }
+
+                let vote = Vote {
+                    vote_type: vote::Type::Precommit,
+                    height,
+                    round: block::Round::default(),
+                    block_id: Some(block_id),
+                    timestamp: Some(self.time),
+                    validator_address: info.address,
+                    validator_index: u32::try_from(index)?.try_into()?,
+                    signature: None,
+                    extension: vec![],
+                    extension_signature: None,
+                };
+                let sign_bytes = vote::SignedVote::from_vote(vote, self.chain_id.clone())
+                    .context("vote without block id or timestamp")?
+                    .sign_bytes();
+                Ok(CommitSig::BlockIdFlagCommit {
+                    validator_address: info.address,
+                    timestamp: self.time,
+                    signature: Some(validator.sign(&sign_bytes)?),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Builds the validator set of `validators`, with the most powerful one as the proposer.
+fn validator_set(validators: &[TestValidator]) -> Result<validator::Set> {
+    let infos = validators
+        .iter()
+        .map(TestValidator::info)
+        .collect::<Result<Vec<_>>>()?;
+    let sorted = validator::Set::without_proposer(infos);
+    let proposer = sorted.validators()[0].clone();
+    Ok(validator::Set::new(
+        sorted.validators().clone(),
+        Some(proposer),
+    ))
+}
+
+/// A deterministic hash for the header fields that are not verified by light clients.
+fn placeholder_hash(field: &str, height: u32) -> Hash {
+    Hash::Sha256(
+        Sha256::new()
+            .chain_update(field)
+            .chain_update(height.to_be_bytes())
+            .finalize()
+            .into(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use ibc_core_commitment_types::{
+        commitment::CommitmentRoot,
+        proto::{ics23::HostFunctionsManager, v1::MerklePath},
+        specs::ProofSpecs,
+    };
+    use sp1_ics07_tendermint_utils::convert_tm_to_ics_merkle_proof;
+    use tendermint_light_client_verifier::{
+        options::Options, types::TrustThreshold, ProdVerifier, Verdict, Verifier,
+    };
+
+    use super::*;
+    use crate::store::IBC_STORE;
+
+    /// A chain whose validators change power, join, leave, rotate their keys and miss blocks.
+    fn changing_chain() -> TestChain {
+        let mut chain = TestChain::new(
+            "testgen-1",
+            vec![
+                TestValidator::new("alice", 100),
+                TestValidator::new("bob", 50),
+                TestValidator::new("carol", 50),
+            ],
+        )
+        .unwrap();
+        chain.produce_blocks(3).unwrap();
+        chain.set_power("alice", 10).unwrap();
+        chain.add_validator(TestValidator::new("dave", 40)).unwrap();
+        chain.produce_block().unwrap();
+        chain.rotate_key("bob", b"bob/5").unwrap();
+        chain.skip_signature("alice");
+        chain.produce_block().unwrap();
+        chain.set_power("dave", 0).unwrap();
+        chain.skip_signature("dave");
+        chain.advance_time(Duration::from_secs(3600));
+        chain.produce_blocks(4).unwrap();
+        chain
+    }
+
+    fn options(chain: &TestChain) -> Options {
+        Options {
+            trust_threshold: TrustThreshold::ONE_THIRD,
+            trusting_period: chain.unbonding_period() * 2 / 3,
+            clock_drift: Duration::from_secs(10),
+        }
+    }
+
+    fn verify(chain: &TestChain, trusted: u32, untrusted: u32) -> Verdict {
+        let trusted = chain.light_block(trusted).unwrap();
+        let untrusted = chain.light_block(untrusted).unwrap();
+        let now = untrusted
+            .signed_header
+            .header
+            .time
+            .checked_add(Duration::from_secs(1))
+            .unwrap();
+        ProdVerifier::default().verify_update_header(
+            untrusted.as_untrusted_state(),
+            trusted.as_trusted_state(),
+            &options(chain),
+            now,
+        )
+    }
+
+    #[test]
+    fn adjacent_light_blocks_verify() {
+        let chain = changing_chain();
+        for height in 2..=chain.latest_height() {
+            let verdict = verify(&chain, height - 1, height);
+            assert!(matches!(verdict, Verdict::Success), "{height}: {verdict:?}");
+        }
+    }
+
+    #[test]
+    fn skipping_light_blocks_verify() {
+        let chain = changing_chain();
+        for (trusted, untrusted) in [(1, 5), (1, 9), (3, 6), (4, 9)] {
+            let verdict = verify(&chain, trusted, untrusted);
+            assert!(
+                matches!(verdict, Verdict::Success),
+                "{trusted} -> {untrusted}: {verdict:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn skipping_without_enough_trust_does_not_verify() {
+        let mut chain =
+            TestChain::new("testgen-1", vec![TestValidator::new("alice", 100)]).unwrap();
+        chain.produce_block().unwrap();
+        chain.rotate_key("alice", b"alice/2").unwrap();
+        chain.produce_blocks(3).unwrap();
+        assert!(matches!(verify(&chain, 1, 4), Verdict::NotEnoughTrust(_)));
+        assert!(matches!(verify(&chain, 1, 2), Verdict::Success));
+    }
+
+    /// Verifies the proof of `key` at `height` against the app hash of the next header, as the
+    /// operator does with the proofs of ABCI queries.
+    fn verify_proof(chain: &TestChain, key: &str, height: u32) -> Vec<u8> {
+        let (value, proof) = chain.prove_ops(key.as_bytes(), height).unwrap();
+        let proof = convert_tm_to_ics_merkle_proof(&proof).unwrap();
+        let app_hash = &chain
+            .light_block(height + 1)
+            .unwrap()
+            .signed_header
+            .header
+            .app_hash;
+        let root = CommitmentRoot::from_bytes(app_hash.as_bytes());
+        let path = MerklePath {
+            key_path: vec![IBC_STORE.to_string(), key.to_string()],
+        };
+        if value.is_empty() {
+            proof
+                .verify_non_membership::<HostFunctionsManager>(
+                    &ProofSpecs::cosmos(),
+                    root.into(),
+                    path,
+                )
+                .unwrap();
+        } else {
+            proof
+                .verify_membership::<HostFunctionsManager>(
+                    &ProofSpecs::cosmos(),
+                    root.into(),
+                    path,
+                    value.clone(),
+                    0,
+                )
+                .unwrap();
+        }
+        value
+    }
+
+    #[test]
+    fn store_proofs_verify_against_the_next_app_hash() {
+        let mut chain =
+            TestChain::new("testgen-1", vec![TestValidator::new("alice", 100)]).unwrap();
+        for (index, key) in ["a", "c", "e", "g", "i"].into_iter().enumerate() {
+            chain
+                .store_mut()
+                .set(key, vec![u8::try_from(index).unwrap() + 1]);
+        }
+        chain.produce_blocks(2).unwrap();
+        chain.store_mut().remove(b"e");
+        chain.store_mut().set("c", vec![42]);
+        chain.produce_blocks(2).unwrap();
+
+        assert_eq!(verify_proof(&chain, "a", 1), vec![1]);
+        assert_eq!(verify_proof(&chain, "c", 1), vec![2]);
+        assert_eq!(verify_proof(&chain, "e", 2), vec![3]);
+        assert_eq!(verify_proof(&chain, "i", 2), vec![5]);
+        assert_eq!(verify_proof(&chain, "c", 3), vec![42]);
+        for key in ["0", "b", "e", "h", "z"] {
+            assert!(verify_proof(&chain, key, 3).is_empty(), "{key}");
+        }
+    }
+}
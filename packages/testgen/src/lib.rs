@@ -0,0 +1,13 @@
+#![doc = include_str!("../README.md")]
+#![deny(missing_docs)]
+#![deny(clippy::nursery, clippy::pedantic, warnings)]
+
+pub mod chain;
+pub mod spec;
+pub mod store;
+pub mod validator;
+
+pub use chain::TestChain;
+pub use spec::ChainSpec;
+pub use store::Store;
+pub use validator::TestValidator;
@@ -0,0 +1,161 @@
+//! Declarative specifications of synthetic chains.
+
+use std::{
+    collections::BTreeMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_with::serde_as;
+use tendermint::Time;
+
+use crate::{
+    chain::{DEFAULT_BLOCK_INTERVAL, DEFAULT_UNBONDING_PERIOD},
+    TestChain, TestValidator,
+};
+
+/// The specification of a [`TestChain`]: its validators, and the changes applied before
+/// producing given heights.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChainSpec {
+    /// The chain identifier.
+    pub chain_id: String,
+    /// The number of blocks to produce.
+    pub blocks: u32,
+    /// The seconds between blocks.
+    #[serde(default = "default_block_interval")]
+    pub block_interval: u64,
+    /// The unbonding period in seconds.
+    #[serde(default = "default_unbonding_period")]
+    pub unbonding_period: u64,
+    /// The UNIX time of the first block. Defaults to the time that puts the latest block at the
+    /// current time, so that its light blocks are within the trusting period of a client
+    /// verifying them now.
+    #[serde(default)]
+    pub genesis_time: Option<i64>,
+    /// The validators of the first block.
+    pub validators: Vec<ValidatorSpec>,
+    /// The changes to apply before producing given heights.
+    #[serde(default)]
+    pub events: Vec<EventSpec>,
+}
+
+/// A validator of a [`ChainSpec`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ValidatorSpec {
+    /// The name of the validator, from which its key is derived.
+    pub name: String,
+    /// The voting power of the validator.
+    pub power: u64,
+}
+
+/// Changes applied to a [`ChainSpec`] chain before producing the block at `height`.
+#[serde_as]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct EventSpec {
+    /// The height of the block the changes are applied before.
+    pub height: u32,
+    /// The new powers of validators by name. A power of 0 removes the validator.
+    pub set_power: BTreeMap<String, u64>,
+    /// The validators to add.
+    pub add_validators: Vec<ValidatorSpec>,
+    /// The names of the validators whose keys are rotated.
+    pub rotate_keys: Vec<String>,
+    /// The names of the validators that do not sign the block.
+    pub absent: Vec<String>,
+    /// Seconds added to the time between the previous block and the block.
+    pub time_gap: u64,
+    /// The hex encoded values to set in the IBC store, by key.
+    #[serde_as(as = "BTreeMap<_, serde_with::hex::Hex>")]
+    pub set: BTreeMap<String, Vec<u8>>,
+    /// The keys to remove from the IBC store.
+    pub remove: Vec<String>,
+}
+
+const fn default_block_interval() -> u64 {
+    DEFAULT_BLOCK_INTERVAL.as_secs()
+}
+
+const fn default_unbonding_period() -> u64 {
+    DEFAULT_UNBONDING_PERIOD.as_secs()
+}
+
+impl ChainSpec {
+    /// Builds the chain and produces its blocks.
+    ///
+    /// # Errors
+    /// Returns an error if an event refers to an unknown validator, or a block cannot be
+    /// produced.
+    pub fn build(&self) -> Result<TestChain> {
+        let validators = self
+            .validators
+            .iter()
+            .map(|validator| TestValidator::new(&validator.name, validator.power))
+            .collect();
+        let mut chain = TestChain::new(&self.chain_id, validators)?
+            .with_block_interval(Duration::from_secs(self.block_interval))
+            .with_unbonding_period(Duration::from_secs(self.unbonding_period))
+            .with_genesis_time(Time::from_unix_timestamp(self.genesis_time()?, 0)?);
+
+        for height in 1..=self.blocks {
+            for event in self.events.iter().filter(|event| event.height == height) {
+                event.apply(&mut chain)?;
+            }
+            chain.produce_block()?;
+        }
+        Ok(chain)
+    }
+
+    /// Gets the UNIX time of the first block: the configured one, or the current time minus the
+    /// block intervals and time gaps up to the latest block.
+    ///
+    /// # Errors
+    /// Returns an error if the system time is before the UNIX epoch or the duration of the
+    /// chain overflows.
+    pub fn genesis_time(&self) -> Result<i64> {
+        if let Some(genesis_time) = self.genesis_time {
+            return Ok(genesis_time);
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let mut duration = u64::from(self.blocks.saturating_sub(1))
+            .checked_mul(self.block_interval)
+            .context("chain duration overflow")?;
+        for event in &self.events {
+            if (1..=self.blocks).contains(&event.height) {
+                duration = duration
+                    .checked_add(event.time_gap)
+                    .context("chain duration overflow")?;
+            }
+        }
+        Ok(i64::try_from(now.saturating_sub(duration))?)
+    }
+}
+
+impl EventSpec {
+    fn apply(&self, chain: &mut TestChain) -> Result<()> {
+        for (name, power) in &self.set_power {
+            chain.set_power(name, *power)?;
+        }
+        for validator in &self.add_validators {
+            chain.add_validator(TestValidator::new(&validator.name, validator.power))?;
+        }
+        for name in &self.rotate_keys {
+            chain.rotate_key(name, format!("{name}/{}", self.height).as_bytes())?;
+        }
+        for name in &self.absent {
+            chain.skip_signature(name);
+        }
+        chain.advance_time(Duration::from_secs(self.time_gap));
+        for (key, value) in &self.set {
+            chain.store_mut().set(key.as_bytes(), value.clone());
+        }
+        for key in &self.remove {
+            chain.store_mut().remove(key.as_bytes());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,337 @@
+//! A Cosmos SDK style multistore, with an IAVL shaped IBC store and ICS-23 proofs.
+
+use std::collections::BTreeMap;
+
+use ibc_core_commitment_types::{
+    merkle::MerkleProof,
+    proto::ics23::{
+        commitment_proof::Proof, CommitmentProof, ExistenceProof, HashOp, InnerOp, LeafOp,
+        LengthOp, NonExistenceProof,
+    },
+};
+use prost::Message;
+use sha2::{Digest, Sha256};
+use tendermint::merkle::proof::{ProofOp, ProofOps};
+
+/// The name of the IBC store in the multistore.
+pub const IBC_STORE: &str = "ibc";
+
+/// The other stores of the multistore. Their contents are not modelled, only their roots.
+const OTHER_STORES: [&str; 4] = ["acc", "bank", "staking", "upgrade"];
+
+/// A key-value store committed with the same hashing as the IAVL store of the Cosmos SDK, inside
+/// a multistore committed as a simple Merkle tree, so that its proofs verify with
+/// `ProofSpecs::cosmos()`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Store {
+    entries: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl Store {
+    /// Sets the value of `key`.
+    pub fn set(&mut self, key: impl Into<Vec<u8>>, value: impl Into<Vec<u8>>) {
+        self.entries.insert(key.into(), value.into());
+    }
+
+    /// Removes `key`.
+    pub fn remove(&mut self, key: &[u8]) {
+        self.entries.remove(key);
+    }
+
+    /// Gets the value of `key`.
+    #[must_use]
+    pub fn get(&self, key: &[u8]) -> Option<&[u8]> {
+        self.entries.get(key).map(Vec::as_slice)
+    }
+
+    /// Computes the app hash of the multistore containing this store at `version`.
+    #[must_use]
+    pub fn app_hash(&self, version: u64) -> [u8; 32] {
+        let (root, _) = self.multistore_proof(version);
+        root
+    }
+
+    /// Proves the membership, or the non-membership if the key is absent, of `key` at
+    /// `version`. Returns the value, empty if absent, and the proof against the app hash.
+    ///
+    /// # Errors
+    /// Returns an error if the store is empty, as there is nothing to prove against.
+    pub fn prove(&self, key: &[u8], version: u64) -> anyhow::Result<(Vec<u8>, MerkleProof)> {
+        anyhow::ensure!(
+            !self.entries.is_empty(),
+            "cannot prove a key of an empty store"
+        );
+        let leaves: Vec<_> = self.entries.iter().collect();
+
+        let (value, store_proof) = match leaves.binary_search_by(|(k, _)| k.as_slice().cmp(key)) {
+            Ok(index) => (
+                leaves[index].1.clone(),
+                Proof::Exist(existence_proof(&leaves, index, version)),
+            ),
+            Err(index) => (
+                vec![],
+                Proof::Nonexist(NonExistenceProof {
+                    key: key.to_vec(),
+                    left: index
+                        .checked_sub(1)
+                        .map(|left| existence_proof(&leaves, left, version)),
+                    right: (index < leaves.len()).then(|| existence_proof(&leaves, index, version)),
+                }),
+            ),
+        };
+        let (_, multistore_proof) = self.multistore_proof(version);
+
+        Ok((
+            value,
+            MerkleProof {
+                proofs: vec![
+                    CommitmentProof {
+                        proof: Some(store_proof),
+                    },
+                    CommitmentProof {
+                        proof: Some(Proof::Exist(multistore_proof)),
+                    },
+                ],
+            },
+        ))
+    }
+
+    /// Proves `key` at `version` like [`Store::prove`], as the proof operations of an ABCI
+    /// query response.
+    ///
+    /// # Errors
+    /// Returns an error if the store is empty.
+    pub fn prove_ops(&self, key: &[u8], version: u64) -> anyhow::Result<(Vec<u8>, ProofOps)> {
+        let (value, proof) = self.prove(key, version)?;
+        let [store_proof, multistore_proof]: [CommitmentProof; 2] = proof
+            .proofs
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("unexpected number of proofs"))?;
+        let ops = vec![
+            ProofOp {
+                field_type: "ics23:iavl".to_string(),
+                key: key.to_vec(),
+                data: store_proof.encode_to_vec(),
+            },
+            ProofOp {
+                field_type: "ics23:simple".to_string(),
+                key: IBC_STORE.as_bytes().to_vec(),
+                data: multistore_proof.encode_to_vec(),
+            },
+        ];
+        Ok((value, ProofOps { ops }))
+    }
+
+    /// Computes the root of the IBC store at `version`. The root of an empty store is empty.
+    fn root(&self, version: u64) -> Vec<u8> {
+        let leaves: Vec<_> = self.entries.iter().collect();
+        if leaves.is_empty() {
+            return vec![];
+        }
+        iavl_hash(&leaves, version).0.to_vec()
+    }
+
+    /// Computes the app hash and the proof of the IBC store root in the multistore.
+    fn multistore_proof(&self, version: u64) -> ([u8; 32], ExistenceProof) {
+        let mut stores: Vec<(&str, Vec<u8>)> = OTHER_STORES
+            .iter()
+            .map(|name| (*name, Sha256::digest(name.as_bytes()).to_vec()))
+            .collect();
+        stores.push((IBC_STORE, self.root(version)));
+        stores.sort_unstable();
+
+        let leaves: Vec<[u8; 32]> = stores
+            .iter()
+            .map(|(name, root)| leaf_hash(&simple_leaf_op(), name.as_bytes(), root))
+            .collect();
+        let index = stores
+            .iter()
+            .position(|(name, _)| *name == IBC_STORE)
+            .expect("the IBC store is in the multistore");
+
+        let proof = ExistenceProof {
+            key: IBC_STORE.as_bytes().to_vec(),
+            value: stores[index].1.clone(),
+            leaf: Some(simple_leaf_op()),
+            path: simple_path(&leaves, index),
+        };
+        (simple_root(&leaves), proof)
+    }
+}
+
+/// Encodes `n` as an unsigned varint, as the lengths of ICS-23 leaves are encoded.
+fn varint(n: u64) -> Vec<u8> {
+    let mut buf = vec![];
+    prost::encoding::encode_varint(n, &mut buf);
+    buf
+}
+
+/// Encodes the height, size and version of an IAVL node. IAVL encodes them as signed zigzag
+/// varints, in which a non-negative `n` is encoded as the unsigned varint of `2n`.
+fn iavl_node_prefix(height: usize, size: usize, version: u64) -> Vec<u8> {
+    [height as u64, size as u64, version]
+        .iter()
+        .flat_map(|n| varint(2 * n))
+        .collect()
+}
+
+fn iavl_leaf_op(version: u64) -> LeafOp {
+    LeafOp {
+        hash: HashOp::Sha256.into(),
+        prehash_key: HashOp::NoHash.into(),
+        prehash_value: HashOp::Sha256.into(),
+        length: LengthOp::VarProto.into(),
+        prefix: iavl_node_prefix(0, 1, version),
+    }
+}
+
+fn simple_leaf_op() -> LeafOp {
+    LeafOp {
+        hash: HashOp::Sha256.into(),
+        prehash_key: HashOp::NoHash.into(),
+        prehash_value: HashOp::Sha256.into(),
+        length: LengthOp::VarProto.into(),
+        prefix: vec![0],
+    }
+}
+
+/// Hashes a leaf as ICS-23 does for `leaf_op`, with a length prefixed key and hashed value.
+fn leaf_hash(leaf_op: &LeafOp, key: &[u8], value: &[u8]) -> [u8; 32] {
+    let value_hash = Sha256::digest(value);
+    let mut hasher = Sha256::new();
+    hasher.update(&leaf_op.prefix);
+    hasher.update(varint(key.len() as u64));
+    hasher.update(key);
+    hasher.update(varint(value_hash.len() as u64));
+    hasher.update(value_hash);
+    hasher.finalize().into()
+}
+
+/// Hashes an inner node as ICS-23 does for `inner_op`.
+fn inner_hash(inner_op: &InnerOp, child: &[u8]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(&inner_op.prefix)
+        .chain_update(child)
+        .chain_update(&inner_op.suffix)
+        .finalize()
+        .into()
+}
+
+/// The inner operation of an IAVL node whose child on the path is on the `left` or right side.
+fn iavl_inner_op(prefix: Vec<u8>, left: &[u8; 32], right: &[u8; 32], on_left: bool) -> InnerOp {
+    if on_left {
+        InnerOp {
+            hash: HashOp::Sha256.into(),
+            prefix: [prefix, vec![32]].concat(),
+            suffix: [&[32][..], right].concat(),
+        }
+    } else {
+        InnerOp {
+            hash: HashOp::Sha256.into(),
+            prefix: [prefix, vec![32], left.to_vec(), vec![32]].concat(),
+            suffix: vec![],
+        }
+    }
+}
+
+type Leaf<'a> = (&'a Vec<u8>, &'a Vec<u8>);
+
+/// Computes the hash and height of the balanced IAVL tree of the sorted `leaves`.
+fn iavl_hash(leaves: &[Leaf<'_>], version: u64) -> ([u8; 32], usize) {
+    if let [(key, value)] = leaves {
+        return (leaf_hash(&iavl_leaf_op(version), key, value), 0);
+    }
+    let (left, right) = leaves.split_at(leaves.len().div_ceil(2));
+    let (left_hash, left_height) = iavl_hash(left, version);
+    let (right_hash, right_height) = iavl_hash(right, version);
+    let height = left_height.max(right_height) + 1;
+    let op = iavl_inner_op(
+        iavl_node_prefix(height, leaves.len(), version),
+        &left_hash,
+        &right_hash,
+        true,
+    );
+    (inner_hash(&op, &left_hash), height)
+}
+
+/// Computes the inner operations from the leaf at `index` to the root, bottom up.
+fn iavl_path(leaves: &[Leaf<'_>], index: usize, version: u64) -> Vec<InnerOp> {
+    if leaves.len() == 1 {
+        return vec![];
+    }
+    let mid = leaves.len().div_ceil(2);
+    let (left, right) = leaves.split_at(mid);
+    let (left_hash, left_height) = iavl_hash(left, version);
+    let (right_hash, right_height) = iavl_hash(right, version);
+    let prefix = iavl_node_prefix(left_height.max(right_height) + 1, leaves.len(), version);
+
+    let on_left = index < mid;
+    let mut path = if on_left {
+        iavl_path(left, index, version)
+    } else {
+        iavl_path(right, index - mid, version)
+    };
+    path.push(iavl_inner_op(prefix, &left_hash, &right_hash, on_left));
+    path
+}
+
+fn existence_proof(leaves: &[Leaf<'_>], index: usize, version: u64) -> ExistenceProof {
+    let (key, value) = leaves[index];
+    ExistenceProof {
+        key: key.clone(),
+        value: value.clone(),
+        leaf: Some(iavl_leaf_op(version)),
+        path: iavl_path(leaves, index, version),
+    }
+}
+
+/// Splits a simple Merkle tree of `n` leaves as Tendermint does: at the largest power of two
+/// smaller than `n`.
+const fn simple_split(n: usize) -> usize {
+    1 << (n - 1).ilog2()
+}
+
+fn simple_inner_op(left: &[u8; 32], right: &[u8; 32], on_left: bool) -> InnerOp {
+    if on_left {
+        InnerOp {
+            hash: HashOp::Sha256.into(),
+            prefix: vec![1],
+            suffix: right.to_vec(),
+        }
+    } else {
+        InnerOp {
+            hash: HashOp::Sha256.into(),
+            prefix: [&[1][..], left].concat(),
+            suffix: vec![],
+        }
+    }
+}
+
+fn simple_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    if let [leaf] = leaves {
+        return *leaf;
+    }
+    let (left, right) = leaves.split_at(simple_split(leaves.len()));
+    let left = simple_root(left);
+    inner_hash(&simple_inner_op(&left, &simple_root(right), true), &left)
+}
+
+fn simple_path(leaves: &[[u8; 32]], index: usize) -> Vec<InnerOp> {
+    if leaves.len() == 1 {
+        return vec![];
+    }
+    let split = simple_split(leaves.len());
+    let (left, right) = leaves.split_at(split);
+    let on_left = index < split;
+    let mut path = if on_left {
+        simple_path(left, index)
+    } else {
+        simple_path(right, index - split)
+    };
+    path.push(simple_inner_op(
+        &simple_root(left),
+        &simple_root(right),
+        on_left,
+    ));
+    path
+}
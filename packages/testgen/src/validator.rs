@@ -0,0 +1,95 @@
+//! Validators with deterministic keys.
+
+use ed25519_consensus::SigningKey;
+use sha2::{Digest, Sha256};
+use tendermint::{account, validator, vote, PublicKey, Signature};
+
+/// A validator of a [`crate::TestChain`], with an Ed25519 key derived from a seed.
+#[derive(Clone, Debug)]
+pub struct TestValidator {
+    name: String,
+    signing_key: SigningKey,
+    power: u64,
+}
+
+impl TestValidator {
+    /// Creates a new validator with `power`, whose key is derived from its `name`.
+    #[must_use]
+    pub fn new(name: impl Into<String>, power: u64) -> Self {
+        let name = name.into();
+        let signing_key = signing_key(name.as_bytes());
+        Self {
+            name,
+            signing_key,
+            power,
+        }
+    }
+
+    /// Replaces the key of the validator with one derived from `seed`.
+    #[must_use]
+    pub fn with_seed(mut self, seed: &[u8]) -> Self {
+        self.signing_key = signing_key(seed);
+        self
+    }
+
+    /// Replaces the voting power of the validator.
+    #[must_use]
+    pub const fn with_power(mut self, power: u64) -> Self {
+        self.power = power;
+        self
+    }
+
+    /// Gets the name of the validator.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gets the voting power of the validator.
+    #[must_use]
+    pub const fn power(&self) -> u64 {
+        self.power
+    }
+
+    /// Gets the public key of the validator.
+    ///
+    /// # Panics
+    /// Panics if the key is not a valid Ed25519 key, which cannot happen.
+    #[must_use]
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey::from_raw_ed25519(&self.signing_key.verification_key().to_bytes())
+            .expect("valid ed25519 key")
+    }
+
+    /// Gets the address of the validator.
+    #[must_use]
+    pub fn address(&self) -> account::Id {
+        account::Id::from(self.public_key())
+    }
+
+    /// Gets the validator info, as it appears in a validator set.
+    ///
+    /// # Errors
+    /// Returns an error if the voting power is out of range.
+    pub fn info(&self) -> anyhow::Result<validator::Info> {
+        Ok(validator::Info::new(
+            self.public_key(),
+            vote::Power::try_from(self.power)?,
+        ))
+    }
+
+    /// Signs `message` with the key of the validator.
+    ///
+    /// # Errors
+    /// Returns an error if the signature cannot be converted.
+    pub fn sign(&self, message: &[u8]) -> anyhow::Result<Signature> {
+        Ok(Signature::try_from(
+            self.signing_key.sign(message).to_bytes().as_slice(),
+        )?)
+    }
+}
+
+fn signing_key(seed: &[u8]) -> SigningKey {
+    let seed: [u8; 32] = Sha256::digest(seed).into();
+    SigningKey::from(seed)
+}